@@ -0,0 +1,475 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use rand::random;
+
+use miden_client::{
+    ClientError, Felt,
+    account::{AccountStorageMode, AccountType},
+    asset::TokenSymbol,
+    note::NoteType,
+    rpc::{Endpoint, NodeRpcClient, TonicRpcClient},
+};
+use miden_lib::account::wallets::create_basic_wallet;
+mod util;
+
+use crate::util::{
+    FaucetFixture, NOTE_TIMEOUT, SetupOptions, account_nonce, assert_client_error, assert_same_tip,
+    await_block_height, await_no_pending_txs, await_note_discovered, balance, consume_notes,
+    create_faucet, force_block, is_connection_error, mint_and_consume, mint_note, named_keystore,
+    new_falcon_auth, reset_named, setup_client, setup_client_with_options, with_harness,
+};
+
+#[tokio::test]
+async fn test_await_block_height() {
+    with_harness(|mut harness| async move {
+        let authenticator = harness.keystore("client");
+        let mut client = harness
+            .client("client", Arc::new(authenticator))
+            .await
+            .unwrap();
+
+        let tip = client.sync_state().await.unwrap().block_num.as_u32();
+        let target = tip + 2;
+        await_block_height(&mut client, target, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(client.get_sync_height().await.unwrap().as_u32() >= target);
+
+        // A height the node will never reach in time must surface as an error
+        let err = await_block_height(&mut client, u32::MAX, Duration::from_secs(2))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ClientError::RecencyConditionError(_)),
+            "expected a timeout error, got: {err:?}"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_setup_without_sync() {
+    with_harness(|mut harness| async move {
+        let authenticator = harness.keystore("client");
+        let mut client = setup_client_with_options(
+            Arc::new(authenticator),
+            &harness.store_name("client"),
+            SetupOptions {
+                sync_on_setup: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // Nothing has been fetched from the node yet
+        assert_eq!(client.get_sync_height().await.unwrap().as_u32(), 0);
+
+        // Make sure the chain has moved past genesis, so the first sync is observable
+        await_block_height(&mut client, 1, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(client.get_sync_height().await.unwrap().as_u32() > 0);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_setup_propagates_sync_result() {
+    with_harness(|mut harness| async move {
+        let authenticator = harness.keystore("client");
+        let mut client = setup_client_with_options(
+            Arc::new(authenticator),
+            &harness.store_name("client"),
+            SetupOptions {
+                propagate_sync_error: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("the initial sync against a running node should succeed");
+
+        assert!(client.get_sync_height().await.unwrap().as_u32() > 0);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_add_account_overwrite() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        // Move Alice's account past its initial state
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
+        .unwrap();
+        let nonce = account_nonce(&mut alice_client, alice.id()).await.unwrap();
+        assert!(nonce > 0);
+
+        // --------------------------------------------------------------------------------
+        // Without overwrite, re-adding a tracked account is refused outright.
+        // --------------------------------------------------------------------------------
+        let err = alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ClientError::AccountAlreadyTracked(_)),
+            "expected the account to be reported as tracked, got: {err:?}"
+        );
+
+        // --------------------------------------------------------------------------------
+        // With overwrite, the client still refuses to roll the account back to a state
+        // older than the one it tracks.
+        // --------------------------------------------------------------------------------
+        let err = alice_client
+            .add_account(&alice, Some(alice_seed), true)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ClientError::AccountNonceTooLow),
+            "expected the stale state to be rejected, got: {err:?}"
+        );
+        assert_eq!(
+            account_nonce(&mut alice_client, alice.id()).await.unwrap(),
+            nonce
+        );
+        assert_eq!(
+            balance(&mut alice_client, alice.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            100
+        );
+
+        // Overwriting with the current state is accepted and leaves it untouched.
+        let current = alice_client
+            .get_account(alice.id())
+            .await
+            .unwrap()
+            .unwrap()
+            .account()
+            .clone();
+        alice_client
+            .add_account(&current, None, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            account_nonce(&mut alice_client, alice.id()).await.unwrap(),
+            nonce
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_sync_state_idempotent() {
+    with_harness(|mut harness| async move {
+        let authenticator = harness.keystore("client");
+        let mut client = harness
+            .client("client", Arc::new(authenticator))
+            .await
+            .unwrap();
+
+        // The node keeps producing blocks, so retry until two syncs land within the same block.
+        // A new block between the syncs is genuine chain progress, not client nondeterminism.
+        let mut attempts = 0;
+        let (first, second) = loop {
+            let first = client.sync_state().await.unwrap();
+            let second = client.sync_state().await.unwrap();
+            if first.block_num == second.block_num {
+                break (first, second);
+            }
+            attempts += 1;
+            assert!(
+                attempts < 5,
+                "the chain tip moved between every pair of syncs"
+            );
+        };
+
+        assert_eq!(second.block_num, first.block_num);
+        assert_eq!(
+            client.get_sync_height().await.unwrap(),
+            second.block_num,
+            "the stored sync height should match the reported chain tip"
+        );
+        assert!(second.new_public_notes.is_empty());
+        assert!(second.committed_notes.is_empty());
+        assert!(second.consumed_notes.is_empty());
+        assert!(second.committed_transactions.is_empty());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_unreachable_endpoint() {
+    with_harness(|mut harness| async move {
+        // Nothing listens on this port
+        let dead_endpoint = Endpoint::new("http".into(), "localhost".into(), Some(1));
+
+        let authenticator = harness.keystore("client");
+        let mut client = setup_client_with_options(
+            Arc::new(authenticator),
+            &harness.store_name("client"),
+            SetupOptions {
+                sync_on_setup: false,
+                endpoint: dead_endpoint,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // The sync must fail fast rather than hang
+        assert_client_error(
+            tokio::time::timeout(Duration::from_secs(10), client.sync_state())
+                .await
+                .expect("syncing against an unreachable endpoint should not hang"),
+            is_connection_error,
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_reset_named() {
+    let suffix = random::<u32>();
+    let names = [
+        format!("named_a_{suffix:08x}"),
+        format!("named_b_{suffix:08x}"),
+    ];
+
+    for name in &names {
+        let keystore = named_keystore(name);
+        // Store a key, so the keystore directory isn't empty
+        new_falcon_auth(&keystore);
+        setup_client(Arc::new(keystore), &format!("{name}_store.sqlite3"))
+            .await
+            .unwrap();
+    }
+
+    reset_named(&names[0]);
+
+    let files_exist = |name: &str| {
+        (
+            Path::new(&format!("{name}_store.sqlite3")).exists(),
+            Path::new("keystore").join(name).exists(),
+        )
+    };
+    assert_eq!(files_exist(&names[0]), (false, false));
+    assert_eq!(
+        files_exist(&names[1]),
+        (true, true),
+        "resetting one name must leave the other's files alone"
+    );
+
+    reset_named(&names[1]);
+    assert_eq!(files_exist(&names[1]), (false, false));
+}
+
+#[tokio::test]
+async fn test_force_block() {
+    with_harness(|mut harness| async move {
+        let authenticator = harness.keystore("client");
+        let auth_scheme = new_falcon_auth(&authenticator);
+        let mut client = harness
+            .client("client", Arc::new(authenticator))
+            .await
+            .unwrap();
+
+        let (wallet, seed) = create_basic_wallet(
+            random(),
+            auth_scheme,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        client
+            .add_account(&wallet, Some(seed), false)
+            .await
+            .unwrap();
+
+        let tip = client.sync_state().await.unwrap().block_num.as_u32();
+        force_block(&mut client, wallet.id()).await.unwrap();
+        assert!(client.get_sync_height().await.unwrap().as_u32() > tip);
+        assert_eq!(account_nonce(&mut client, wallet.id()).await.unwrap(), 1);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_injected_rpc_api() {
+    with_harness(|mut harness| async move {
+        // Nothing listens on this port, so only the injected RPC client can reach the node
+        let dead_endpoint = Endpoint::new("http".into(), "localhost".into(), Some(1));
+        let rpc_api: Arc<dyn NodeRpcClient + Send> =
+            Arc::new(TonicRpcClient::new(&Endpoint::localhost(), 100));
+
+        let authenticator = harness.keystore("client");
+        let mut client = setup_client_with_options(
+            Arc::new(authenticator),
+            &harness.store_name("client"),
+            SetupOptions {
+                endpoint: dead_endpoint,
+                rpc_api: Some(rpc_api),
+                propagate_sync_error: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("the injected RPC client should be used instead of the endpoint");
+
+        assert!(client.get_sync_height().await.unwrap().as_u32() > 0);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_sync_after_many_blocks() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("CU", 1_000).await.unwrap();
+
+        // --------------------------------------------------------------------------------
+        // While Alice's client stays offline, she is sent a note and the chain moves on.
+        // --------------------------------------------------------------------------------
+        let offline_height = alice_client.get_sync_height().await.unwrap().as_u32();
+        let note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            35,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        await_no_pending_txs(&mut faucet_client, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            force_block(&mut faucet_client, faucet_account.id())
+                .await
+                .unwrap();
+        }
+
+        // --------------------------------------------------------------------------------
+        // A single sync catches up with all of it.
+        // --------------------------------------------------------------------------------
+        let sync_summary = alice_client.sync_state().await.unwrap();
+        assert!(
+            sync_summary.block_num.as_u32() > offline_height + 3,
+            "the sync should catch up with every block produced while offline"
+        );
+        assert_same_tip(&mut alice_client, &mut faucet_client).await;
+
+        // The note was committed blocks ago, but its tag still leads Alice's client to it
+        await_note_discovered(&mut alice_client, note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice.id(), vec![note.id()])
+            .await
+            .unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_concurrent_setup_isolated_stores() {
+    with_harness(|mut harness| async move {
+        let keystore_a = harness.keystore("a");
+        let keystore_b = harness.keystore("b");
+        let auth_scheme_a = new_falcon_auth(&keystore_a);
+        let auth_scheme_b = new_falcon_auth(&keystore_b);
+
+        let store_a = harness.store_name("a");
+        let store_b = harness.store_name("b");
+        let (client_a, client_b) = tokio::join!(
+            setup_client(Arc::new(keystore_a), &store_a),
+            setup_client(Arc::new(keystore_b), &store_b),
+        );
+        let mut client_a = client_a.expect("setting up the first client should not contend");
+        let mut client_b = client_b.expect("setting up the second client should not contend");
+
+        let mut wallets = Vec::new();
+        for (client, auth_scheme) in [
+            (&mut client_a, auth_scheme_a),
+            (&mut client_b, auth_scheme_b),
+        ] {
+            let (wallet, seed) = create_basic_wallet(
+                random(),
+                auth_scheme,
+                AccountType::RegularAccountImmutableCode,
+                AccountStorageMode::Public,
+            )
+            .unwrap();
+            client
+                .add_account(&wallet, Some(seed), false)
+                .await
+                .unwrap();
+            wallets.push(wallet.id());
+        }
+
+        // Each store only holds the account added through its own client
+        for (client, own_id, other_id) in [
+            (&mut client_a, wallets[0], wallets[1]),
+            (&mut client_b, wallets[1], wallets[0]),
+        ] {
+            let tracked: Vec<_> = client
+                .get_account_headers()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|(header, _)| header.id())
+                .collect();
+            assert_eq!(tracked, vec![own_id]);
+            assert!(client.get_account(other_id).await.unwrap().is_none());
+        }
+    })
+    .await;
+}
@@ -1,350 +1,1261 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use rand::random;
 
 use miden_client::{
-    ClientError, Felt, Word,
+    Felt, Word,
     account::{AccountStorageMode, AccountType},
     asset::{FungibleAsset, TokenSymbol},
-    auth::AuthSecretKey,
-    keystore::FilesystemKeyStore,
-    note::{Note, NoteAssets, NoteExecutionHint, NoteFile, NoteMetadata, NoteTag, NoteType},
-    transaction::{TransactionRequestBuilder, TransactionScript},
+    note::{Note, NoteExecutionHint, NoteExecutionMode, NoteId, NoteRecipient, NoteTag},
+    store::TransactionFilter,
+    transaction::{TransactionRequestBuilder, TransactionStatus},
 };
-use miden_lib::{
-    AuthScheme,
-    account::{faucets::create_basic_fungible_faucet, wallets::create_basic_wallet},
-    note::utils::build_p2id_recipient,
-    transaction::TransactionKernel,
-};
-use miden_objects::crypto::dsa::rpo_falcon512;
+use miden_lib::account::wallets::create_basic_wallet;
 mod util;
 
-use crate::util::{DrainFaucet, reset_store, setup_client};
+use crate::util::{
+    DRAIN_AMOUNT, DRAIN_AUX, DrainFaucet, FaucetFixture, NOTE_TIMEOUT, SubmitError, TestMode,
+    account_nonce, assert_client_error, assert_faucet_metadata, assert_p2id_recipient,
+    assert_submit_rejected, await_block_height, await_no_pending_txs, await_note_consumed,
+    await_note_discovered, await_note_on_chain, balance, component_drain_request,
+    component_mint_request, consume_against_faucet, consume_notes, create_faucet,
+    create_faucet_with_component, default_drain_note_tag, drain_output_note, emit_note,
+    expected_drain_output_note, faucet_consume_request, faucet_issuance, hardened_faucet_component,
+    holds_key, import_created_notes, is_hardened_distribute_refused, is_spent_note_error,
+    mint_and_consume, new_falcon_auth, note_aux, note_serial_nums, pending_tx_count, prove_timed,
+    run_drain, run_drain_with_mode, setup_client, share_account_state, with_harness,
+};
+
+/// Generous upper bound on the time it takes to prove the drain transaction.
+const MAX_DRAIN_PROVING_TIME: Duration = Duration::from_secs(60);
 
 #[tokio::test]
 async fn test_drain_faucet() {
-    // clean the DB for the test
-    reset_store().await;
+    with_harness(|mut harness| async move {
+        // --------------------------------------------------------------------------------
+        // Setup authenticator / keystore
+        //
+        // This needs to happen before the client is created, since we need to init the client with this authenticator.
+        // --------------------------------------------------------------------------------
+        // Faucet authenticator (shared for both faucets)
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
 
-    // --------------------------------------------------------------------------------
-    // Setup keys for the accounts
-    // --------------------------------------------------------------------------------
-    // Faucet A
-    let secret_key_faucet = rpo_falcon512::SecretKey::new();
-    let pub_key_faucet = secret_key_faucet.public_key();
-    let auth_scheme_faucet: AuthScheme = AuthScheme::RpoFalcon512 {
-        pub_key: pub_key_faucet,
-    };
-
-    // Alice
-    let secret_key_alice = rpo_falcon512::SecretKey::new();
-    let pub_key_alice = secret_key_alice.public_key();
-    let auth_scheme_alice: AuthScheme = AuthScheme::RpoFalcon512 {
-        pub_key: pub_key_alice,
-    };
+        // Alice authenticator
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
 
-    // --------------------------------------------------------------------------------
-    // Setup authenticator / keystore
-    //
-    // This needs to happen before the client is created, since we need to init the client with this authenticator.
-    // --------------------------------------------------------------------------------
-    // Faucet authenticator (shared for both faucets)
-    let faucet_authenticator = FilesystemKeyStore::new("keystore/faucets".into()).unwrap();
-    faucet_authenticator
-        .add_key(&AuthSecretKey::RpoFalcon512(secret_key_faucet))
+        // Alice can sign for her wallet, but never for the faucet. Tracking the faucet's public
+        // state is all she needs to run the drain against it, so the drain is a genuine exploit
+        // rather than a misuse of the owner's key.
+        assert!(holds_key(&alice_authenticator, &auth_scheme_alice));
+        assert!(!holds_key(&alice_authenticator, &auth_scheme_faucet));
+        assert!(holds_key(&faucet_authenticator, &auth_scheme_faucet));
+
+        // --------------------------------------------------------------------------------
+        // Create client instances
+        // --------------------------------------------------------------------------------
+        // Each client gets its own store, removed along with the harness
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        // Sync state to get chain info instead of get_latest_epoch_block
+        faucet_client.sync_state().await.unwrap();
+        println!("Got sync state");
+
+        // For now let's use the same max supply for both tokens
+        let max_supply = Felt::new(1_000);
+
+        let token_symbol = "NP";
+        let token_symbol = TokenSymbol::try_from(token_symbol).unwrap();
+
+        let decimals = 2u8;
+
+        // --------------------------------------------------------------------------------
+        // Create faucet accounts
+        // --------------------------------------------------------------------------------
+        let (faucet_account, faucet_seed) = create_faucet(
+            token_symbol,
+            decimals,
+            max_supply,
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
         .unwrap();
 
-    // Alice authenticator
-    let alice_authenticator = FilesystemKeyStore::new("keystore/alice".into()).unwrap();
-    alice_authenticator
-        .add_key(&AuthSecretKey::RpoFalcon512(secret_key_alice))
+        // --------------------------------------------------------------------------------
+        // Create user/wallet accounts
+        // --------------------------------------------------------------------------------
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
         .unwrap();
 
-    // --------------------------------------------------------------------------------
-    // Create client instances
-    // --------------------------------------------------------------------------------
-    let mut faucet_client = setup_client(Arc::new(faucet_authenticator), "faucet_store.sqlite3")
+        // --------------------------------------------------------------------------------
+        // Track accounts in the client.
+        //
+        // Not the same as adding the keys to the authenticator. A client can track accounts without having their signing keys.
+        // --------------------------------------------------------------------------------
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Mint assets from the faucet account for alice, and have her consume them
+        // --------------------------------------------------------------------------------
+        println!("Minting assets for Alice...");
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
         .await
         .unwrap();
-    let mut alice_client = setup_client(Arc::new(alice_authenticator), "alice_store.sqlite3")
+        println!("Submitted consume transaction for Alice");
+
+        // The mint deployed the faucet, so Alice's copy is fetched from the node. Its metadata
+        // must match what the faucet was created with.
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
+        assert_faucet_metadata(
+            &mut alice_client,
+            faucet_account.id(),
+            token_symbol,
+            decimals,
+            max_supply,
+        )
+        .await;
+
+        // --------------------------------------------------------------------------------
+        // Now Alice attempts to drain the faucet
+        // First, she emits a note that locks a small amount of the asset for her.
+        // That asset will eventually be burned by the faucet account, in a consume-note
+        // transaction Alice executes against the faucet.
+        // --------------------------------------------------------------------------------
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let expected_output_note = run_drain(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+        )
         .await
         .unwrap();
+        println!("Alice's note found on chain");
 
-    // Sync state to get chain info instead of get_latest_epoch_block
-    faucet_client.sync_state().await.unwrap();
-    println!("Got sync state");
+        // The claim below relies on the output note being executable at any time, so check the
+        // hint as committed on chain rather than the one of the locally rebuilt note.
+        let committed_output_note = alice_client
+            .get_input_note(expected_output_note.id())
+            .await
+            .unwrap()
+            .expect("the drain output note should have been imported");
+        let committed_metadata = committed_output_note
+            .metadata()
+            .expect("a committed note should carry its metadata");
+        assert_eq!(
+            committed_metadata.execution_hint(),
+            NoteExecutionHint::Always
+        );
 
-    // For now let's use the same max supply for both tokens
-    let max_supply = Felt::new(1_000);
+        // Now Alice can claim the drained asset
+        println!("Claiming drained asset...");
+        let claim_request = TransactionRequestBuilder::new()
+            .build_consume_notes(vec![expected_output_note.id()])
+            .unwrap();
 
-    let token_symbol = "NP";
-    let token_symbol = TokenSymbol::try_from(token_symbol).unwrap();
+        let claim_tx_result = alice_client
+            .new_transaction(alice.id(), claim_request)
+            .await
+            .unwrap();
 
-    let decimals = 2u8;
+        alice_client
+            .submit_transaction(claim_tx_result)
+            .await
+            .unwrap();
 
-    // --------------------------------------------------------------------------------
-    // Create faucet accounts
-    // --------------------------------------------------------------------------------
-    let (faucet_account, faucet_seed) = create_basic_fungible_faucet(
-        random(),
-        token_symbol,
-        decimals,
-        max_supply,
-        AccountStorageMode::Public,
-        auth_scheme_faucet,
-    )
-    .unwrap();
+        // Emitting the drain note and draining the faucet were committed before their notes showed
+        // up on chain, so only the claim is still pending. In the end all of them are committed.
+        assert_eq!(pending_tx_count(&mut alice_client).await.unwrap(), 1);
+        await_no_pending_txs(&mut alice_client, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(pending_tx_count(&mut alice_client).await.unwrap(), 0);
 
-    // --------------------------------------------------------------------------------
-    // Create user/wallet accounts
-    // --------------------------------------------------------------------------------
-    let (alice, alice_seed) = create_basic_wallet(
-        random(),
-        auth_scheme_alice,
-        AccountType::RegularAccountImmutableCode,
-        AccountStorageMode::Public,
-    )
-    .unwrap();
+        // Consuming the minted note, emitting the drain note, draining and claiming
+        let transactions = alice_client
+            .get_transactions(TransactionFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(transactions.len(), 4);
+        assert!(
+            transactions
+                .iter()
+                .all(|tx| matches!(tx.status, TransactionStatus::Committed { .. })),
+            "all of Alice's transactions should be committed: {transactions:?}"
+        );
 
-    // --------------------------------------------------------------------------------
-    // Track accounts in the client.
-    //
-    // Not the same as adding the keys to the authenticator. A client can track accounts without having their signing keys.
-    // --------------------------------------------------------------------------------
-    faucet_client
-        .add_account(&faucet_account, Some(faucet_seed), false)
+        // Wait for the transaction to be confirmed
+        alice_client.sync_state().await.unwrap();
+
+        // Check Alice's balance
+        let alice_account = alice_client.get_account(alice.id()).await.unwrap().unwrap();
+        let alice_balance = alice_account
+            .account()
+            .vault()
+            .get_balance(faucet_account.id())
+            .unwrap();
+        assert_eq!(
+            alice_balance, DRAIN_AMOUNT,
+            "Alice should have received 250 tokens from the drained faucet"
+        );
+
+        // The claim spent the output note, so it can't be consumed a second time
+        await_note_consumed(&mut alice_client, expected_output_note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        let reclaim_request = TransactionRequestBuilder::new()
+            .build_consume_notes(vec![expected_output_note.id()])
+            .unwrap();
+        assert_client_error(
+            alice_client
+                .new_transaction(alice.id(), reclaim_request)
+                .await,
+            is_spent_note_error,
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_faucet_issuance() {
+    with_harness(|mut harness| async move {
+        let max_supply = 1_000;
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("NP", max_supply).await.unwrap();
+
+        let minted = 100;
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            minted,
+        )
         .await
         .unwrap();
 
-    alice_client
-        .add_account(&alice, Some(alice_seed), false)
+        faucet_client.sync_state().await.unwrap();
+        let issuance_before = faucet_issuance(&mut faucet_client, faucet_account.id())
+            .await
+            .unwrap();
+        assert_eq!(issuance_before, minted);
+
+        // --------------------------------------------------------------------------------
+        // Alice burns everything she holds and has the faucet distribute to her instead.
+        // --------------------------------------------------------------------------------
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
+        let burned = minted;
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), burned)
+            .unwrap()
+            .into();
+        run_drain(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+        )
         .await
         .unwrap();
 
-    // --------------------------------------------------------------------------------
-    // Mint assets from the faucet account for alice
-    // --------------------------------------------------------------------------------
-    println!("Minting assets for Alice...");
-    let mint_asset_a: FungibleAsset = FungibleAsset::new(faucet_account.id(), 100).unwrap();
+        // The faucet's public state is refreshed from the node on sync.
+        faucet_client.sync_state().await.unwrap();
+        let issuance_after = faucet_issuance(&mut faucet_client, faucet_account.id())
+            .await
+            .unwrap();
 
-    let transaction_request_a = TransactionRequestBuilder::new()
-        .build_mint_fungible_asset(
-            mint_asset_a,
+        println!("Issuance before drain: {issuance_before}, after drain: {issuance_after}");
+        assert_eq!(
+            issuance_after,
+            issuance_before - burned + DRAIN_AMOUNT,
+            "issuance should account for the burned and the distributed amount"
+        );
+        assert!(
+            issuance_after <= max_supply,
+            "drain must not inflate the issuance beyond the max supply"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_faucet_prove_only() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("PO", 1_000).await.unwrap();
+
+        let minted = 100;
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
             alice.id(),
-            NoteType::Public,
-            faucet_client.rng(),
+            minted,
         )
+        .await
         .unwrap();
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
 
-    let tx_result_a = faucet_client
-        .new_transaction(faucet_account.id(), transaction_request_a)
+        // --------------------------------------------------------------------------------
+        // Both steps of the drain are proven, neither is submitted.
+        // --------------------------------------------------------------------------------
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), minted)
+            .unwrap()
+            .into();
+        let output_note = run_drain_with_mode(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+            TestMode::ProveOnly,
+        )
         .await
         .unwrap();
-    let note_for_alice = tx_result_a.created_notes().iter().next().unwrap();
+        assert_eq!(
+            output_note.id(),
+            expected_drain_output_note(faucet_account.id(), alice.id()).id()
+        );
+
+        alice_client.sync_state().await.unwrap();
+        assert_eq!(pending_tx_count(&mut alice_client).await.unwrap(), 0);
+        assert_eq!(
+            balance(&mut alice_client, alice.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            minted,
+            "a drain that was only proven must leave Alice's vault alone"
+        );
+        assert!(
+            alice_client
+                .get_input_note(output_note.id())
+                .await
+                .unwrap()
+                .is_none(),
+            "the faucet should not have distributed anything"
+        );
+
+        faucet_client.sync_state().await.unwrap();
+        assert_eq!(
+            faucet_issuance(&mut faucet_client, faucet_account.id())
+                .await
+                .unwrap(),
+            minted
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_note_tag_discovery() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("NP", 1_000).await.unwrap();
+        // The observer doesn't track any accounts, so it has no keys either
+        let observer_authenticator = harness.keystore("observer");
+        let mut observer_client = harness
+            .client("observer", Arc::new(observer_authenticator))
+            .await
+            .unwrap();
 
-    faucet_client
-        .submit_transaction(tx_result_a.clone())
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
         .await
         .unwrap();
-    println!("Submitted mint transaction for Alice");
-
-    // Loop for up to 10 seconds, with 1 sec intervals, until import_note succeeds
-    println!("Waiting for Alice's note to be confirmed on chain...");
-    let start_time = std::time::Instant::now();
-    let timeout = std::time::Duration::from_secs(10);
-    let mut notes_for_alice = Vec::new();
-
-    while start_time.elapsed() < timeout {
-        let note_file = NoteFile::NoteId(note_for_alice.id());
-        match alice_client.import_note(note_file).await {
-            Ok(note) => {
-                notes_for_alice.push(note);
-                alice_client.sync_state().await.unwrap();
-                println!("Alice's note found on chain, breaking");
-                break;
-            }
-            Err(ClientError::NoteNotFoundOnChain(_)) => {
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                alice_client.sync_state().await.unwrap();
-            }
-            _ => {
-                panic!("Failed");
-            }
-        }
-    }
 
-    // Only panic if we've timed out without finding the note
-    if notes_for_alice.is_empty() {
-        panic!("Notes not found on chain after 10 seconds");
-    }
+        // --------------------------------------------------------------------------------
+        // Alice emits the drain note under a tag nobody else uses, so that only a client
+        // subscribed to it can discover the note.
+        // --------------------------------------------------------------------------------
+        let use_case = random::<u16>() >> 2;
+        let drain_note_tag =
+            NoteTag::for_public_use_case(use_case, 0, NoteExecutionMode::Local).unwrap();
+        observer_client.add_note_tag(drain_note_tag).await.unwrap();
 
-    // Need to have Alice consume the notes created by the faucet account
-    println!("Building consume transaction for Alice...");
-    let consume_request_a = TransactionRequestBuilder::new()
-        .build_consume_notes(notes_for_alice)
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let drain_note_request = TransactionRequestBuilder::new()
+            .drain_faucet(alice.id(), asset_to_burn, drain_note_tag, None)
+            .build()
+            .unwrap();
+        let drain_note_tx_result = alice_client
+            .new_transaction(alice.id(), drain_note_request)
+            .await
+            .unwrap();
+        let drain_note_id = drain_note_tx_result
+            .created_notes()
+            .iter()
+            .next()
+            .unwrap()
+            .id();
+        alice_client
+            .submit_transaction(drain_note_tx_result)
+            .await
+            .unwrap();
+
+        let discovered_note =
+            await_note_discovered(&mut observer_client, drain_note_id, NOTE_TIMEOUT)
+                .await
+                .unwrap();
+        assert_eq!(
+            discovered_note.metadata().unwrap().tag(),
+            drain_note_tag,
+            "the drain note should carry the tag it was requested with"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_consume_drain_output_with_wrong_serial_num() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("NP", 1_000).await.unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
         .unwrap();
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
 
-    let tx_result_a = alice_client
-        .new_transaction(alice.id(), consume_request_a)
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let output_note = run_drain(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+        )
         .await
         .unwrap();
 
-    alice_client.submit_transaction(tx_result_a).await.unwrap();
-    println!("Submitted consume transaction for Alice");
+        // --------------------------------------------------------------------------------
+        // Alice rebuilds the faucet's output note by hand, but gets the serial number wrong.
+        // The P2ID script only checks the consuming account against the note inputs, so the
+        // transaction executes and proves fine. The resulting note id however doesn't exist
+        // on chain, which the node only detects once the transaction is submitted.
+        // --------------------------------------------------------------------------------
+        let wrong_recipient = NoteRecipient::new(
+            [Felt::new(1), Felt::new(0), Felt::new(0), Felt::new(0)],
+            output_note.script().clone(),
+            output_note.inputs().clone(),
+        );
+        let mismatched_note = Note::new(
+            output_note.assets().clone(),
+            *output_note.metadata(),
+            wrong_recipient,
+        );
+        assert_ne!(mismatched_note.id(), output_note.id());
 
-    alice_client
-        .import_account_by_id(faucet_account.id())
+        let consume_request = TransactionRequestBuilder::new()
+            .with_unauthenticated_input_notes([(mismatched_note, None)])
+            .build()
+            .unwrap();
+        let tx_result = alice_client
+            .new_transaction(alice.id(), consume_request)
+            .await
+            .unwrap();
+
+        // The note was never created, so it can't have been consumed either
+        let rejection = assert_submit_rejected(&mut alice_client, tx_result).await;
+        assert!(
+            matches!(rejection, SubmitError::Other(_)),
+            "expected the note to be rejected as unknown, got: {rejection:?}"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_private_faucet() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Private,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
         .await
         .unwrap();
 
-    // --------------------------------------------------------------------------------
-    // Now Alice attempts to drain the faucet
-    // First, she emits a note that locks a small amount of the asset for her.
-    // That asset will eventually be burned by the faucet account.
-    // --------------------------------------------------------------------------------
+        // --------------------------------------------------------------------------------
+        // Unlike the public case, Alice can't import the faucet from the node, which only
+        // stores the commitment of private accounts. She needs its full state out-of-band.
+        // --------------------------------------------------------------------------------
+        faucet_client.sync_state().await.unwrap();
+        share_account_state(&mut faucet_client, &mut alice_client, faucet_account.id())
+            .await
+            .unwrap();
 
-    let asset_to_burn = mint_asset_a.into();
-    let malicious_note_request = TransactionRequestBuilder::new()
-        .drain_faucet(alice.id(), asset_to_burn)
-        .build()
+        // The nonce-bump trick doesn't depend on the storage mode, so the drain goes through.
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let output_note = run_drain(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+        )
+        .await
         .unwrap();
 
-    let malicious_note_tx_result = alice_client
-        .new_transaction(alice.id(), malicious_note_request)
+        let claim_request = TransactionRequestBuilder::new()
+            .build_consume_notes(vec![output_note.id()])
+            .unwrap();
+        let claim_tx_result = alice_client
+            .new_transaction(alice.id(), claim_request)
+            .await
+            .unwrap();
+        alice_client
+            .submit_transaction(claim_tx_result)
+            .await
+            .unwrap();
+        alice_client.sync_state().await.unwrap();
+
+        let alice_account = alice_client.get_account(alice.id()).await.unwrap().unwrap();
+        let alice_balance = alice_account
+            .account()
+            .vault()
+            .get_balance(faucet_account.id())
+            .unwrap();
+        assert_eq!(
+            alice_balance, DRAIN_AMOUNT,
+            "Alice should have drained the private faucet as well"
+        );
+
+        // --------------------------------------------------------------------------------
+        // The owner, however, can't learn the faucet's new state from the node: its copy
+        // no longer matches the on-chain commitment, so the client locks the account.
+        // --------------------------------------------------------------------------------
+        faucet_client.sync_state().await.unwrap();
+        let faucet_record = faucet_client
+            .get_account(faucet_account.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            faucet_record.is_locked(),
+            "the owner's stale copy of the private faucet should be locked"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_burn_only() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("NP", 1_000).await.unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
         .await
         .unwrap();
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
+        let nonce_before = account_nonce(&mut faucet_client, faucet_account.id())
+            .await
+            .unwrap();
 
-    alice_client
-        .submit_transaction(malicious_note_tx_result.clone())
+        // --------------------------------------------------------------------------------
+        // Alice emits a note which only burns part of her tokens, and consumes it against
+        // the faucet.
+        // --------------------------------------------------------------------------------
+        let burned = 40;
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), burned)
+            .unwrap()
+            .into();
+        let burn_note_request = TransactionRequestBuilder::new()
+            .burn_only(alice.id(), asset_to_burn)
+            .build()
+            .unwrap();
+        let burn_note_id = emit_note(&mut alice_client, alice.id(), burn_note_request)
+            .await
+            .unwrap();
+
+        let burn_tx_result =
+            consume_against_faucet(&mut alice_client, faucet_account.id(), burn_note_id)
+                .await
+                .unwrap();
+        assert!(
+            burn_tx_result.created_notes().is_empty(),
+            "a pure burn must not create any note"
+        );
+
+        // Wait for the burn to be committed before reading the faucet's public state
+        await_no_pending_txs(&mut alice_client, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        faucet_client.sync_state().await.unwrap();
+        let issuance = faucet_issuance(&mut faucet_client, faucet_account.id())
+            .await
+            .unwrap();
+        assert_eq!(
+            issuance,
+            100 - burned,
+            "burning should decrease the issuance"
+        );
+
+        // The drain relies on the burn alone bumping the faucet's nonce, which lets the
+        // transaction pass the kernel's epilogue check without the faucet's key
+        assert_eq!(
+            account_nonce(&mut faucet_client, faucet_account.id())
+                .await
+                .unwrap(),
+            nonce_before + 1,
+            "a burn without any distribution should still bump the faucet's nonce"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_note_serial_nums_unique() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let auth_scheme_other_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        // Both faucets sign with the same keystore, but each is run by its own client, which draws
+        // the serial numbers of the notes it mints from its own RNG.
+        let faucet_authenticator = Arc::new(faucet_authenticator);
+        let mut faucet_client =
+            setup_client(faucet_authenticator.clone(), &harness.store_name("faucet"))
+                .await
+                .unwrap();
+        let mut other_faucet_client =
+            setup_client(faucet_authenticator, &harness.store_name("other_faucet"))
+                .await
+                .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (other_faucet_account, other_faucet_seed) = create_faucet(
+            TokenSymbol::try_from("OP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_other_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        other_faucet_client
+            .add_account(&other_faucet_account, Some(other_faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Create as many notes as possible in one run: a mint from each faucet client, a
+        // burn-only note and the drain note along with the note the faucet distributes.
+        // --------------------------------------------------------------------------------
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
+        .unwrap();
+        mint_and_consume(
+            &mut other_faucet_client,
+            other_faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
         .await
         .unwrap();
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
+
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 10).unwrap().into();
+        let burn_note_request = TransactionRequestBuilder::new()
+            .burn_only(alice.id(), asset_to_burn)
+            .build()
+            .unwrap();
+        let burn_note_id = emit_note(&mut alice_client, alice.id(), burn_note_request)
+            .await
+            .unwrap();
+        consume_against_faucet(&mut alice_client, faucet_account.id(), burn_note_id)
+            .await
+            .unwrap();
 
-    let note_for_alice = malicious_note_tx_result
-        .created_notes()
-        .iter()
-        .next()
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 50).unwrap().into();
+        run_drain(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+        )
+        .await
         .unwrap();
 
-    // --------------------------------------------------------------------------------
-    // Need to fetch the freshly created note.
-    // --------------------------------------------------------------------------------
+        // --------------------------------------------------------------------------------
+        // Every distinct note must have its own serial number.
+        // --------------------------------------------------------------------------------
+        let mut serial_nums = note_serial_nums(&mut faucet_client).await.unwrap();
+        serial_nums.extend(note_serial_nums(&mut other_faucet_client).await.unwrap());
+        serial_nums.extend(note_serial_nums(&mut alice_client).await.unwrap());
 
-    let mut notes_for_alice = Vec::new();
-    let start_time = std::time::Instant::now();
-    while start_time.elapsed() < timeout {
-        let note_file = NoteFile::NoteId(note_for_alice.id());
-        match alice_client.import_note(note_file).await {
-            Ok(note) => {
-                notes_for_alice.push(note);
-                alice_client.sync_state().await.unwrap();
-                println!("Alice's note found on chain, breaking");
-                break;
-            }
-            Err(ClientError::NoteNotFoundOnChain(_)) => {
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                alice_client.sync_state().await.unwrap();
-            }
-            Err(e) => {
-                panic!("Failed: {:?}", e);
+        let mut seen = BTreeMap::new();
+        for (note_id, serial_num) in &serial_nums {
+            if let Some(other_id) = seen.insert(serial_num.map(|felt| felt.as_int()), *note_id) {
+                panic!("notes {other_id} and {note_id} share the serial number {serial_num:?}");
             }
         }
-    }
-    if notes_for_alice.is_empty() {
-        panic!("Notes not found on chain after 10 seconds");
-    }
+        assert!(
+            serial_nums.len() >= 5,
+            "expected both mint notes and the burn, drain and output notes to be recorded, got {}",
+            serial_nums.len()
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_proving_time() {
+    with_harness(|mut harness| async move {
+    let FaucetFixture {
+        mut faucet_client,
+        faucet_account,
+        mut alice_client,
+        alice,
+    } = harness.faucet_fixture("NP", 1_000).await.unwrap();
+
+    mint_and_consume(
+        &mut faucet_client,
+        faucet_account.id(),
+        &mut alice_client,
+        alice.id(),
+        100,
+    )
+    .await
+    .unwrap();
+    alice_client
+        .import_account_by_id(faucet_account.id())
+        .await
+        .unwrap();
+
+    let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+    let drain_note_request = TransactionRequestBuilder::new()
+        .drain_faucet(alice.id(), asset_to_burn, default_drain_note_tag(), None)
+        .build()
+        .unwrap();
+    let drain_note_id = emit_note(&mut alice_client, alice.id(), drain_note_request)
+        .await
+        .unwrap();
 
     // --------------------------------------------------------------------------------
-    // Now Alice executed a consume-note transaction against the faucet.
-    // This should work, since the `burn` first bumps the nonce, so
-    // the epilogue check of "changing account state -> nonce bumped" is satisfied.
+    // Burning and distributing in a single note script is the heaviest transaction of
+    // the suite. Prove it locally, without submitting, and keep an eye on the time.
     // --------------------------------------------------------------------------------
-
-    let expected_output_note = Note::new(
-        NoteAssets::new(vec![
-            FungibleAsset::new(faucet_account.id(), 250).unwrap().into(),
-        ])
-        .unwrap(),
-        NoteMetadata::new(
+    let drain_tx_result = alice_client
+        .new_transaction(
             faucet_account.id(),
-            NoteType::Public,
-            NoteTag::from_account_id(alice.id()),
-            NoteExecutionHint::Always,
-            Felt::new(27),
+            faucet_consume_request(drain_note_id).unwrap(),
         )
-        .unwrap(),
-        build_p2id_recipient(alice.id(), Word::default()).unwrap(),
+        .await
+        .unwrap();
+    let (_, proving_time) = prove_timed(&mut alice_client, &drain_tx_result)
+        .await
+        .unwrap();
+
+    println!("Proving the drain transaction took {proving_time:?}");
+    assert!(
+        proving_time < MAX_DRAIN_PROVING_TIME,
+        "proving the drain transaction took {proving_time:?}, over the {MAX_DRAIN_PROVING_TIME:?} budget"
     );
+    })
+    .await;
+}
 
-    let drain_request = TransactionRequestBuilder::new()
-        .with_custom_script(
-            TransactionScript::compile(
-                "begin\npush.1\ndrop\nend",
-                TransactionKernel::assembler().with_debug_mode(true),
-            )
-            .unwrap(),
+#[tokio::test]
+async fn test_reclaim_drain_note() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("NP", 1_000).await.unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
         )
-        .build_consume_notes(notes_for_alice)
+        .await
         .unwrap();
 
-    let drain_tx_result = alice_client
-        .new_transaction(faucet_account.id(), drain_request)
-        .await
+        // --------------------------------------------------------------------------------
+        // Alice locks her tokens in a reclaimable drain note, but the drain itself never
+        // happens. Once the reclaim height passes, she takes the tokens back.
+        // --------------------------------------------------------------------------------
+        let tip = alice_client.sync_state().await.unwrap().block_num.as_u32();
+        let reclaim_height = tip + 5;
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let drain_note_request = TransactionRequestBuilder::new()
+            .drain_faucet(
+                alice.id(),
+                asset_to_burn,
+                default_drain_note_tag(),
+                Some(reclaim_height),
+            )
+            .build()
+            .unwrap();
+        let drain_note_id = emit_note(&mut alice_client, alice.id(), drain_note_request)
+            .await
+            .unwrap();
+        assert_eq!(
+            balance(&mut alice_client, alice.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            0
+        );
+
+        await_block_height(&mut alice_client, reclaim_height, Duration::from_secs(60))
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice.id(), vec![drain_note_id])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            balance(&mut alice_client, alice.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            100,
+            "Alice should have reclaimed the asset locked in the drain note"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_to_many() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        // The other recipients only need ids to be distributed to
+        let others_authenticator = harness.keystore("bob");
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
         .unwrap();
 
-    alice_client
-        .submit_transaction(drain_tx_result)
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
         .await
         .unwrap();
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
 
-    // Wait for the note to be confirmed on chain
-    let start_time = std::time::Instant::now();
-    while start_time.elapsed() < timeout {
-        let note_file = NoteFile::NoteId(expected_output_note.id());
-        match alice_client.import_note(note_file).await {
-            Ok(_) => {
-                alice_client.sync_state().await.unwrap();
-                println!("Alice's note found on chain, breaking");
-                break;
-            }
-            Err(ClientError::NoteNotFoundOnChain(_)) => {
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                alice_client.sync_state().await.unwrap();
-            }
-            Err(e) => {
-                panic!("Failed: {:?}", e);
-            }
+        let mut recipients = vec![(alice.id(), 50)];
+        for amount in [60, 70] {
+            let (victim, _) = create_basic_wallet(
+                random(),
+                new_falcon_auth(&others_authenticator),
+                AccountType::RegularAccountImmutableCode,
+                AccountStorageMode::Public,
+            )
+            .unwrap();
+            recipients.push((victim.id(), amount));
         }
-    }
 
-    // Now Alice can claim the drained asset
-    println!("Claiming drained asset...");
-    let claim_request = TransactionRequestBuilder::new()
-        .build_consume_notes(vec![expected_output_note.id()])
+        // --------------------------------------------------------------------------------
+        // A single drain note has the faucet distribute to all three recipients.
+        // --------------------------------------------------------------------------------
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let drain_note_request = TransactionRequestBuilder::new()
+            .drain_to_many(alice.id(), asset_to_burn, recipients.clone())
+            .build()
+            .unwrap();
+        let drain_note_id = emit_note(&mut alice_client, alice.id(), drain_note_request)
+            .await
+            .unwrap();
+
+        let drain_tx_result =
+            consume_against_faucet(&mut alice_client, faucet_account.id(), drain_note_id)
+                .await
+                .unwrap();
+
+        let expected_notes: Vec<Note> = recipients
+            .iter()
+            .map(|&(receiver_id, amount)| {
+                drain_output_note(faucet_account.id(), receiver_id, amount)
+            })
+            .collect();
+        let created_ids: Vec<NoteId> = drain_tx_result
+            .created_notes()
+            .iter()
+            .map(|note| note.id())
+            .collect();
+        assert_eq!(
+            created_ids,
+            expected_notes.iter().map(Note::id).collect::<Vec<_>>(),
+            "the faucet should distribute one note per recipient, in order"
+        );
+
+        // The note id commits to its assets, so finding each note on chain proves its amount
+        let imported = import_created_notes(&mut alice_client, &drain_tx_result, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(imported.len(), expected_notes.len());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_hardened_faucet() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        // Same parameters as the vulnerable faucet of `test_drain_faucet`, patched component
+        let faucet_component =
+            hardened_faucet_component(TokenSymbol::try_from("NP").unwrap(), 2, Felt::new(1_000));
+        let (faucet_account, faucet_seed) = create_faucet_with_component(
+            faucet_component.clone(),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
         .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
 
-    let claim_tx_result = alice_client
-        .new_transaction(alice.id(), claim_request)
+        // Minting from a transaction script, without consuming notes, still works
+        let mint_request = component_mint_request(&faucet_component, alice.id(), 100).unwrap();
+        let mint_tx_result = faucet_client
+            .new_transaction(faucet_account.id(), mint_request)
+            .await
+            .unwrap();
+        faucet_client
+            .submit_transaction(mint_tx_result)
+            .await
+            .unwrap();
+        let minted_note = drain_output_note(faucet_account.id(), alice.id(), 100);
+        let minted_note_id = await_note_on_chain(&mut alice_client, minted_note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice.id(), vec![minted_note_id])
+            .await
+            .unwrap();
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // The same drain attempt as against the basic faucet must now fail.
+        // --------------------------------------------------------------------------------
+        // The drain note calls the patched `distribute`, so it is the guard which must stop it
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let drain_note_request =
+            component_drain_request(&faucet_component, alice.id(), asset_to_burn).unwrap();
+        let drain_note_id = emit_note(&mut alice_client, alice.id(), drain_note_request)
+            .await
+            .unwrap();
+
+        assert_client_error(
+            consume_against_faucet(&mut alice_client, faucet_account.id(), drain_note_id).await,
+            is_hardened_distribute_refused,
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_output_recipient_digest() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("NP", 1_000).await.unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
         .await
         .unwrap();
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
 
-    alice_client
-        .submit_transaction(claim_tx_result)
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let output_note = run_drain(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+        )
         .await
         .unwrap();
 
-    // Wait for the transaction to be confirmed
-    alice_client.sync_state().await.unwrap();
+        // The output note was imported from the chain, so this checks what `distribute` emitted
+        assert_p2id_recipient(
+            &mut alice_client,
+            output_note.id(),
+            alice.id(),
+            Word::default(),
+        )
+        .await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drain_output_aux() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("NP", 1_000).await.unwrap();
 
-    // Check Alice's balance
-    let alice_account = alice_client.get_account(alice.id()).await.unwrap().unwrap();
-    let alice_balance = alice_account
-        .account()
-        .vault()
-        .get_balance(faucet_account.id())
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
         .unwrap();
-    assert_eq!(
-        alice_balance, 250,
-        "Alice should have received 250 tokens from the drained faucet"
-    );
+        alice_client
+            .import_account_by_id(faucet_account.id())
+            .await
+            .unwrap();
+
+        let asset_to_burn = FungibleAsset::new(faucet_account.id(), 100).unwrap().into();
+        let output_note = run_drain(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            asset_to_burn,
+        )
+        .await
+        .unwrap();
+
+        // The output note was imported from the chain, so this is the aux `distribute` emitted
+        assert_eq!(
+            note_aux(&mut alice_client, output_note.id()).await.unwrap(),
+            Felt::new(DRAIN_AUX)
+        );
+    })
+    .await;
 }
@@ -0,0 +1,913 @@
+use std::{sync::Arc, time::Duration};
+
+use rand::random;
+
+use miden_client::{
+    ClientError, Felt,
+    account::{AccountStorageMode, AccountType},
+    asset::{FungibleAsset, TokenSymbol},
+    note::{NoteDetails, NoteFile, NoteTag, NoteType},
+    transaction::TransactionRequestBuilder,
+    utils::Deserializable,
+};
+use miden_lib::{account::wallets::create_basic_wallet, note::create_p2id_note};
+mod util;
+
+use crate::util::{
+    FAUCET_SYSDATA_SLOT, FaucetFixture, FaucetSetupError, ISSUANCE_ELEMENT_INDEX, NOTE_TIMEOUT,
+    SetupOptions, TestMode, assert_client_error, assert_faucet_metadata, await_note_committed,
+    await_note_discovered, await_note_on_chain, create_faucet, create_faucet_from_symbol,
+    create_keyless_faucet, created_note_details, created_note_type, faucet_issuance, import_notes,
+    is_max_supply_exceeded, is_missing_key_error, mint_note, new_falcon_auth, pending_tx_count,
+    prove_and_submit, prove_and_submit_with_mode, read_storage_slot, setup_client_with_options,
+    throwaway_auth_scheme, tracked_faucets, with_harness,
+};
+
+#[tokio::test]
+async fn test_tracked_faucets() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let alice_authenticator = harness.keystore("alice");
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator.clone()))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator.clone()))
+            .await
+            .unwrap();
+
+        let mut faucet_ids = Vec::new();
+        for symbol in ["A", "B"] {
+            let (faucet, seed) = create_faucet(
+                TokenSymbol::try_from(symbol).unwrap(),
+                2,
+                Felt::new(1_000),
+                AccountStorageMode::Public,
+                new_falcon_auth(&faucet_authenticator),
+            )
+            .unwrap();
+            faucet_client
+                .add_account(&faucet, Some(seed), false)
+                .await
+                .unwrap();
+            faucet_ids.push(faucet.id());
+        }
+
+        // A wallet on the faucet client must not be reported as a faucet
+        let (wallet, wallet_seed) = create_basic_wallet(
+            random(),
+            new_falcon_auth(&faucet_authenticator),
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        faucet_client
+            .add_account(&wallet, Some(wallet_seed), false)
+            .await
+            .unwrap();
+
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            new_falcon_auth(&alice_authenticator),
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        let mut tracked = tracked_faucets(&mut faucet_client).await.unwrap();
+        tracked.sort();
+        faucet_ids.sort();
+        assert_eq!(
+            tracked, faucet_ids,
+            "exactly the two faucets should be tracked"
+        );
+
+        assert!(
+            tracked_faucets(&mut alice_client).await.unwrap().is_empty(),
+            "Alice's client should not track any faucet"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_shared_keystore_concurrent_mints() {
+    with_harness(|mut harness| async move {
+        // Both faucet keys live in the same keystore directory, but each client loads it
+        // through its own keystore instance.
+        let keystore = harness.keystore("faucet");
+        let auth_scheme_a = new_falcon_auth(&keystore);
+        let auth_scheme_b = new_falcon_auth(&keystore);
+        let keystore_a = harness.keystore("faucet");
+        let keystore_b = harness.keystore("faucet");
+        let alice_authenticator = harness.keystore("alice");
+
+        let mut client_a = harness
+            .client("faucet_a", Arc::new(keystore_a))
+            .await
+            .unwrap();
+        let mut client_b = harness
+            .client("faucet_b", Arc::new(keystore_b))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator.clone()))
+            .await
+            .unwrap();
+
+        let (faucet_a, seed_a) = create_faucet(
+            TokenSymbol::try_from("A").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_a,
+        )
+        .unwrap();
+        let (faucet_b, seed_b) = create_faucet(
+            TokenSymbol::try_from("B").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_b,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            new_falcon_auth(&alice_authenticator),
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        client_a
+            .add_account(&faucet_a, Some(seed_a), false)
+            .await
+            .unwrap();
+        client_b
+            .add_account(&faucet_b, Some(seed_b), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Both clients sign with keys from the shared directory at the same time.
+        // --------------------------------------------------------------------------------
+        let (note_a, note_b) = tokio::join!(
+            mint_note(
+                &mut client_a,
+                faucet_a.id(),
+                alice.id(),
+                10,
+                NoteType::Public
+            ),
+            mint_note(
+                &mut client_b,
+                faucet_b.id(),
+                alice.id(),
+                20,
+                NoteType::Public
+            ),
+        );
+        let note_a = note_a.expect("minting from faucet A with the shared keystore failed");
+        let note_b = note_b.expect("minting from faucet B with the shared keystore failed");
+
+        await_note_on_chain(&mut alice_client, note_a.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        await_note_on_chain(&mut alice_client, note_b.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_import_public_note_without_sync() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        // Bob's client is created before the mint and never synced afterwards
+        let mut bob_client = setup_client_with_options(
+            Arc::new(bob_authenticator),
+            &harness.store_name("bob"),
+            SetupOptions {
+                sync_on_setup: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("US").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (bob, _) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        let note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            bob.id(),
+            10,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        // Wait on the faucet's side, so Bob's client stays untouched until the import
+        await_note_on_chain(&mut faucet_client, note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // A public note is fetched from the node by id, no prior sync is needed.
+        // --------------------------------------------------------------------------------
+        let imported_id = bob_client
+            .import_note(NoteFile::NoteId(note.id()))
+            .await
+            .expect("importing a public note should not require a synced client");
+        assert_eq!(imported_id, note.id());
+        assert!(
+            bob_client
+                .get_input_note(note.id())
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(bob_client.get_sync_height().await.unwrap().as_u32(), 0);
+    })
+    .await;
+}
+
+#[test]
+fn test_create_faucet_storage_modes() {
+    for storage_mode in [AccountStorageMode::Public, AccountStorageMode::Private] {
+        let (faucet, _) = create_faucet(
+            TokenSymbol::try_from("SM").unwrap(),
+            2,
+            Felt::new(1_000),
+            storage_mode,
+            throwaway_auth_scheme(),
+        )
+        .unwrap();
+
+        assert!(faucet.is_faucet());
+        assert_eq!(faucet.id().storage_mode(), storage_mode);
+        assert!(faucet.is_new());
+    }
+}
+
+#[tokio::test]
+async fn test_import_notes_partial() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("IN").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, _) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        let mut minted_ids = Vec::new();
+        for amount in [10, 20] {
+            let note = mint_note(
+                &mut faucet_client,
+                faucet_account.id(),
+                alice.id(),
+                amount,
+                NoteType::Public,
+            )
+            .await
+            .unwrap();
+            minted_ids.push(note.id());
+        }
+
+        // A well-formed note that is never submitted, so the node doesn't know about it
+        let bogus_note = create_p2id_note(
+            faucet_account.id(),
+            alice.id(),
+            vec![FungibleAsset::new(faucet_account.id(), 30).unwrap().into()],
+            NoteType::Public,
+            Felt::new(0),
+            faucet_client.rng(),
+        )
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // The bogus note sits between the two real ones and must not hide either of them.
+        // --------------------------------------------------------------------------------
+        let (imported, failed) = import_notes(
+            &mut alice_client,
+            &[minted_ids[0], bogus_note.id(), minted_ids[1]],
+            NOTE_TIMEOUT,
+        )
+        .await;
+
+        let imported_ids: Vec<_> = imported.iter().map(|note| note.id()).collect();
+        assert_eq!(imported_ids, minted_ids);
+        assert_eq!(failed.len(), 1);
+        let (failed_id, err) = &failed[0];
+        assert_eq!(*failed_id, bogus_note.id());
+        assert!(
+            matches!(err, ClientError::NoteNotFoundOnChain(_)),
+            "expected the bogus note to be missing on chain, got: {err:?}"
+        );
+    })
+    .await;
+}
+
+#[test]
+fn test_create_faucet_invalid_symbol() {
+    // Token symbols are limited to six characters
+    let err = create_faucet_from_symbol(
+        "TOOLONG",
+        2,
+        Felt::new(1_000),
+        AccountStorageMode::Public,
+        throwaway_auth_scheme(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&err, FaucetSetupError::InvalidSymbol { symbol, .. } if symbol == "TOOLONG"),
+        "expected an invalid symbol error, got: {err:?}"
+    );
+
+    create_faucet_from_symbol(
+        "MAXLEN",
+        2,
+        Felt::new(1_000),
+        AccountStorageMode::Public,
+        throwaway_auth_scheme(),
+    )
+    .expect("a six-character symbol should be accepted");
+}
+
+#[tokio::test]
+async fn test_mint_up_to_max_supply() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        // The recipients never consume their notes, they only need ids
+        let others_authenticator = harness.keystore("bob");
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+
+        let max_supply = 1_000;
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("MS").unwrap(),
+            2,
+            Felt::new(max_supply),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // The whole supply is minted across two recipients.
+        // --------------------------------------------------------------------------------
+        let mut recipients = Vec::new();
+        for _ in 0..2 {
+            let (recipient, _) = create_basic_wallet(
+                random(),
+                new_falcon_auth(&others_authenticator),
+                AccountType::RegularAccountImmutableCode,
+                AccountStorageMode::Public,
+            )
+            .unwrap();
+            recipients.push(recipient.id());
+        }
+        for (recipient_id, amount) in recipients.iter().zip([600, max_supply - 600]) {
+            mint_note(
+                &mut faucet_client,
+                faucet_account.id(),
+                *recipient_id,
+                amount,
+                NoteType::Public,
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            faucet_issuance(&mut faucet_client, faucet_account.id())
+                .await
+                .unwrap(),
+            max_supply
+        );
+
+        // Not even a single token more can be minted
+        assert_client_error(
+            mint_note(
+                &mut faucet_client,
+                faucet_account.id(),
+                recipients[0],
+                1,
+                NoteType::Public,
+            )
+            .await,
+            is_max_supply_exceeded,
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_mint_note_types() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("MT", 1_000).await.unwrap();
+
+        for note_type in [NoteType::Public, NoteType::Private] {
+            let after_block_num = faucet_client.get_sync_height().await.unwrap();
+            let mint_request = TransactionRequestBuilder::new()
+                .build_mint_fungible_asset(
+                    FungibleAsset::new(faucet_account.id(), 10).unwrap(),
+                    alice.id(),
+                    note_type,
+                    faucet_client.rng(),
+                )
+                .unwrap();
+            let mint_tx_result = faucet_client
+                .new_transaction(faucet_account.id(), mint_request)
+                .await
+                .unwrap();
+            let note_id = mint_tx_result.created_notes().get_note(0).id();
+            assert_eq!(created_note_type(&mint_tx_result, note_id), Some(note_type));
+            let serialized_details = created_note_details(&mint_tx_result, note_id).unwrap();
+            faucet_client
+                .submit_transaction(mint_tx_result)
+                .await
+                .unwrap();
+
+            // ----------------------------------------------------------------------------
+            // A public note is found on the node by id, a private one only from its details.
+            // ----------------------------------------------------------------------------
+            match note_type {
+                NoteType::Public => {
+                    await_note_on_chain(&mut alice_client, note_id, NOTE_TIMEOUT)
+                        .await
+                        .unwrap();
+                }
+                _ => {
+                    // The node only stores the header of a private note
+                    alice_client
+                        .import_note(NoteFile::NoteDetails {
+                            details: NoteDetails::read_from_bytes(&serialized_details).unwrap(),
+                            after_block_num,
+                            tag: Some(NoteTag::from_account_id(alice.id())),
+                        })
+                        .await
+                        .unwrap();
+                    await_note_committed(&mut alice_client, note_id, NOTE_TIMEOUT)
+                        .await
+                        .unwrap();
+                }
+            }
+
+            let record = alice_client.get_input_note(note_id).await.unwrap().unwrap();
+            assert_eq!(
+                record.metadata().map(|metadata| metadata.note_type()),
+                Some(note_type)
+            );
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_prove_and_submit_mint() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("PS").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, _) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        let mint_request = TransactionRequestBuilder::new()
+            .build_mint_fungible_asset(
+                FungibleAsset::new(faucet_account.id(), 10).unwrap(),
+                alice.id(),
+                NoteType::Public,
+                faucet_client.rng(),
+            )
+            .unwrap();
+        let note_id = mint_request
+            .expected_output_notes()
+            .next()
+            .expect("the mint request should create a note")
+            .id();
+
+        let tx_id = prove_and_submit(&mut faucet_client, faucet_account.id(), mint_request)
+            .await
+            .unwrap();
+        println!("Submitted mint transaction {tx_id}");
+
+        await_note_on_chain(&mut alice_client, note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_mint_note_discovered_by_tag() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("TD").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice's client doesn't track her account yet, so only the explicit subscription
+        // to her tag can pick up the note.
+        // --------------------------------------------------------------------------------
+        alice_client
+            .add_note_tag(NoteTag::from_account_id(alice.id()))
+            .await
+            .unwrap();
+
+        let note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            10,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        assert_eq!(note.metadata().tag(), NoteTag::from_account_id(alice.id()));
+
+        // No `import_note`: syncing alone has to find it
+        await_note_discovered(&mut alice_client, note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+        let consumable_ids: Vec<_> = alice_client
+            .get_consumable_notes(Some(alice.id()))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(note, _)| note.id())
+            .collect();
+        assert!(
+            consumable_ids.contains(&note.id()),
+            "the discovered note should be consumable by Alice"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_read_issuance_slot() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("IS").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, _) = create_basic_wallet(
+            random(),
+            new_falcon_auth(&alice_authenticator),
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        let sysdata =
+            read_storage_slot(&mut faucet_client, faucet_account.id(), FAUCET_SYSDATA_SLOT)
+                .await
+                .unwrap();
+        assert_eq!(sysdata[ISSUANCE_ELEMENT_INDEX].as_int(), 0);
+
+        mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            25,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+
+        let sysdata =
+            read_storage_slot(&mut faucet_client, faucet_account.id(), FAUCET_SYSDATA_SLOT)
+                .await
+                .unwrap();
+        assert_eq!(sysdata[ISSUANCE_ELEMENT_INDEX].as_int(), 25);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_track_faucet_without_signing_key() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let alice_authenticator = harness.keystore("alice");
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator.clone()))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed, faucet_key) = create_keyless_faucet(
+            TokenSymbol::try_from("KL").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let (alice, _) = create_basic_wallet(
+            random(),
+            new_falcon_auth(&alice_authenticator),
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        // Tracking an account only needs its state, not its key
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Reads work off the tracked state, but minting needs a signature the client can't produce.
+        // --------------------------------------------------------------------------------
+        assert_faucet_metadata(
+            &mut faucet_client,
+            faucet_account.id(),
+            TokenSymbol::try_from("KL").unwrap(),
+            2,
+            Felt::new(1_000),
+        )
+        .await;
+        assert_eq!(
+            faucet_issuance(&mut faucet_client, faucet_account.id())
+                .await
+                .unwrap(),
+            0
+        );
+
+        assert_client_error(
+            mint_note(
+                &mut faucet_client,
+                faucet_account.id(),
+                alice.id(),
+                10,
+                NoteType::Public,
+            )
+            .await,
+            is_missing_key_error,
+        );
+
+        // --------------------------------------------------------------------------------
+        // Once the key is in the keystore, the same client can mint.
+        // --------------------------------------------------------------------------------
+        faucet_authenticator.add_key(&faucet_key).unwrap();
+        mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            10,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            faucet_issuance(&mut faucet_client, faucet_account.id())
+                .await
+                .unwrap(),
+            10
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_prove_only_mint() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("PO").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, _) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        let mint_request = TransactionRequestBuilder::new()
+            .build_mint_fungible_asset(
+                FungibleAsset::new(faucet_account.id(), 10).unwrap(),
+                alice.id(),
+                NoteType::Public,
+                faucet_client.rng(),
+            )
+            .unwrap();
+        let note_id = mint_request
+            .expected_output_notes()
+            .next()
+            .expect("the mint request should create a note")
+            .id();
+
+        prove_and_submit_with_mode(
+            &mut faucet_client,
+            faucet_account.id(),
+            mint_request,
+            TestMode::ProveOnly,
+        )
+        .await
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Nothing reached the node, and the faucet's tracked state didn't move either.
+        // --------------------------------------------------------------------------------
+        assert_eq!(pending_tx_count(&mut faucet_client).await.unwrap(), 0);
+        assert_eq!(
+            faucet_issuance(&mut faucet_client, faucet_account.id())
+                .await
+                .unwrap(),
+            0
+        );
+        let err = await_note_on_chain(&mut alice_client, note_id, Duration::from_secs(3))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ClientError::NoteNotFoundOnChain(_)),
+            "the proven mint should never reach the node, got: {err:?}"
+        );
+    })
+    .await;
+}
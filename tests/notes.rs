@@ -0,0 +1,251 @@
+use std::time::{Duration, Instant};
+
+use rand::random;
+
+use miden_client::{
+    Felt, Word,
+    account::{AccountId, AccountStorageMode, AccountType},
+    asset::{Asset, FungibleAsset, TokenSymbol},
+    note::{NoteExecutionHint, NoteMetadata, NoteScript, NoteTag, NoteType},
+    transaction::SwapTransactionData,
+};
+use miden_lib::{
+    account::wallets::create_basic_wallet, note::utils::build_p2id_recipient,
+    transaction::TransactionKernel,
+};
+use miden_objects::{
+    NoteError, testing::account_id::ACCOUNT_ID_REGULAR_PUBLIC_ACCOUNT_IMMUTABLE_CODE,
+};
+mod util;
+
+use crate::util::{
+    build_p2idr_recipient, build_swap_note, check_note_script, create_faucet, custom_note,
+    fungible_from_decimal, p2id_inputs, p2idr_inputs, swap_data_from_decimal, swap_note_assets,
+    test_assembler, throwaway_auth_scheme,
+};
+
+/// Number of scripts compiled to compare the shared assembler against fresh ones.
+const NUM_SCRIPTS: u32 = 100;
+/// Number of rounds of the comparison, of which the fastest is kept.
+const NUM_ROUNDS: u32 = 3;
+/// Factor by which the shared assembler may exceed the fresh ones before the comparison fails.
+const TIMING_MARGIN: f64 = 1.5;
+
+/// Returns the id of a fresh wallet, which is never deployed.
+fn wallet_id() -> AccountId {
+    let (wallet, _) = create_basic_wallet(
+        random(),
+        throwaway_auth_scheme(),
+        AccountType::RegularAccountImmutableCode,
+        AccountStorageMode::Public,
+    )
+    .unwrap();
+
+    wallet.id()
+}
+
+/// Returns the id of a fresh fungible faucet, which is never deployed.
+fn faucet_id() -> AccountId {
+    let (faucet, _) = create_faucet(
+        TokenSymbol::try_from("CN").unwrap(),
+        2,
+        Felt::new(1_000),
+        AccountStorageMode::Public,
+        throwaway_auth_scheme(),
+    )
+    .unwrap();
+
+    faucet.id()
+}
+
+fn note_script_source(i: u32) -> String {
+    format!("begin\npush.{i}\ndrop\nend")
+}
+
+#[test]
+fn test_shared_assembler_compile_time() {
+    // Warm up the shared assembler so that its one-off construction isn't measured.
+    let _ = test_assembler();
+
+    // Keep the fastest of several rounds, so a single slow round doesn't skew the comparison.
+    let mut shared_elapsed = Duration::MAX;
+    let mut fresh_elapsed = Duration::MAX;
+    for _ in 0..NUM_ROUNDS {
+        let start_time = Instant::now();
+        for i in 0..NUM_SCRIPTS {
+            NoteScript::compile(note_script_source(i), test_assembler().clone()).unwrap();
+        }
+        shared_elapsed = shared_elapsed.min(start_time.elapsed());
+
+        let start_time = Instant::now();
+        for i in 0..NUM_SCRIPTS {
+            let assembler = TransactionKernel::assembler().with_debug_mode(true);
+            NoteScript::compile(note_script_source(i), assembler).unwrap();
+        }
+        fresh_elapsed = fresh_elapsed.min(start_time.elapsed());
+    }
+
+    println!(
+        "Compiled {NUM_SCRIPTS} note scripts: {shared_elapsed:?} with the shared assembler, \
+         {fresh_elapsed:?} with a fresh assembler per script (best of {NUM_ROUNDS} rounds)"
+    );
+    // The margin absorbs timing noise on a loaded machine. Only a shared assembler which is
+    // clearly slower than rebuilding one per script fails the test.
+    assert!(
+        shared_elapsed < fresh_elapsed.mul_f64(TIMING_MARGIN),
+        "reusing the shared assembler should not be slower than rebuilding it per script"
+    );
+}
+
+#[test]
+fn test_p2id_inputs() {
+    let receiver_id =
+        AccountId::try_from(ACCOUNT_ID_REGULAR_PUBLIC_ACCOUNT_IMMUTABLE_CODE).unwrap();
+
+    let recipient = build_p2id_recipient(receiver_id, Word::default()).unwrap();
+    assert_eq!(&p2id_inputs(receiver_id).unwrap(), recipient.inputs());
+}
+
+#[test]
+fn test_p2idr_inputs() {
+    let receiver_id =
+        AccountId::try_from(ACCOUNT_ID_REGULAR_PUBLIC_ACCOUNT_IMMUTABLE_CODE).unwrap();
+    let reclaim_height = 42;
+
+    let recipient = build_p2idr_recipient(receiver_id, reclaim_height, Word::default()).unwrap();
+    let inputs = p2idr_inputs(receiver_id, reclaim_height).unwrap();
+    assert_eq!(&inputs, recipient.inputs());
+
+    // The reclaim height is the only thing added on top of the P2ID inputs
+    let p2id_inputs = p2id_inputs(receiver_id).unwrap();
+    assert_eq!(&inputs.values()[..2], p2id_inputs.values());
+    assert_eq!(inputs.values()[2], Felt::from(reclaim_height));
+}
+
+#[test]
+fn test_custom_note() {
+    let sender_id = wallet_id();
+    let assets = vec![FungibleAsset::new(faucet_id(), 10).unwrap().into()];
+    let script_src = "begin\n    push.1 drop\n    push.2 drop\nend";
+
+    let note = custom_note(
+        script_src,
+        assets.clone(),
+        build_p2id_recipient(sender_id, Word::default()).unwrap(),
+        NoteMetadata::new(
+            sender_id,
+            NoteType::Public,
+            NoteTag::from_account_id(sender_id),
+            NoteExecutionHint::Always,
+            Felt::new(0),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let expected_script = NoteScript::compile(script_src, test_assembler().clone()).unwrap();
+    assert_eq!(note.script().root(), expected_script.root());
+    assert_eq!(note.assets().iter().copied().collect::<Vec<_>>(), assets);
+    // The recipient's serial number and inputs are kept, only its script is replaced
+    assert_eq!(note.serial_num(), Word::default());
+    assert_eq!(note.inputs(), &p2id_inputs(sender_id).unwrap());
+}
+
+#[test]
+fn test_check_note_script() {
+    let script = check_note_script(&note_script_source(7)).unwrap();
+    let expected = NoteScript::compile(note_script_source(7), test_assembler().clone()).unwrap();
+    assert_eq!(script.root(), expected.root());
+
+    let err = check_note_script("begin\n    push.1\n    not_an_instruction\nend").unwrap_err();
+    assert!(
+        matches!(err, NoteError::NoteScriptAssemblyError(_)),
+        "expected an assembly error, got: {err:?}"
+    );
+    assert!(
+        !err.to_string().is_empty(),
+        "the error should explain why the script doesn't compile"
+    );
+}
+
+#[test]
+fn test_fungible_from_decimal() {
+    let faucet_id = faucet_id();
+
+    assert_eq!(
+        fungible_from_decimal(faucet_id, 1.50, 2).unwrap().amount(),
+        150
+    );
+    assert_eq!(
+        fungible_from_decimal(faucet_id, 1.5, 6).unwrap().amount(),
+        1_500_000
+    );
+    // 1.15 isn't exactly representable, rounding keeps it from becoming 114
+    assert_eq!(
+        fungible_from_decimal(faucet_id, 1.15, 2).unwrap().amount(),
+        115
+    );
+    assert_eq!(
+        fungible_from_decimal(faucet_id, 7.0, 0).unwrap().amount(),
+        7
+    );
+}
+
+#[test]
+fn test_swap_data_from_decimal() {
+    let sender_id = wallet_id();
+    let (offered_faucet, requested_faucet) = (faucet_id(), faucet_id());
+
+    // 1.50 NP with 2 decimals for 0.75 of a token with 6 decimals
+    let swap_data = swap_data_from_decimal(
+        sender_id,
+        offered_faucet,
+        1.50,
+        2,
+        requested_faucet,
+        0.75,
+        6,
+    )
+    .unwrap();
+
+    assert_eq!(swap_data.account_id(), sender_id);
+    let offered_asset = swap_data.offered_asset().unwrap_fungible();
+    assert_eq!(offered_asset.faucet_id(), offered_faucet);
+    assert_eq!(offered_asset.amount(), 150);
+    let requested_asset = swap_data.requested_asset().unwrap_fungible();
+    assert_eq!(requested_asset.faucet_id(), requested_faucet);
+    assert_eq!(requested_asset.amount(), 750_000);
+}
+
+#[test]
+fn test_build_swap_within_one_faucet() {
+    let (sender_id, faucet_id) = (wallet_id(), faucet_id());
+    let offered_asset: Asset = FungibleAsset::new(faucet_id, 10).unwrap().into();
+    let requested_asset: Asset = FungibleAsset::new(faucet_id, 5).unwrap().into();
+
+    // Offering 10 A for 5 A isn't rejected, the SWAP note simply carries both sides.
+    let swap_data = SwapTransactionData::new(sender_id, offered_asset, requested_asset);
+    let swap_note = build_swap_note(&swap_data).unwrap();
+
+    assert_eq!(
+        swap_note_assets(&swap_note),
+        (offered_asset, requested_asset)
+    );
+}
+
+#[test]
+fn test_build_swap_for_nothing() {
+    let sender_id = wallet_id();
+    let (offered_faucet, requested_faucet) = (faucet_id(), faucet_id());
+    let offered_asset: Asset = FungibleAsset::new(offered_faucet, 10).unwrap().into();
+    let requested_asset: Asset = FungibleAsset::new(requested_faucet, 0).unwrap().into();
+
+    // Requesting 0 B isn't rejected either, the note requests an empty asset of the faucet.
+    let swap_data = SwapTransactionData::new(sender_id, offered_asset, requested_asset);
+    let swap_note = build_swap_note(&swap_data).unwrap();
+
+    let (locked, requested) = swap_note_assets(&swap_note);
+    assert_eq!(locked, offered_asset);
+    assert_eq!(requested.unwrap_fungible().faucet_id(), requested_faucet);
+    assert_eq!(requested.unwrap_fungible().amount(), 0);
+}
@@ -0,0 +1,1785 @@
+use std::{fs, path::Path, sync::Arc, time::Duration};
+
+use rand::random;
+
+use miden_client::{
+    Felt,
+    account::{AccountStorageMode, AccountType},
+    asset::{Asset, FungibleAsset, TokenSymbol},
+    crypto::FeltRng,
+    note::{
+        Note, NoteAssets, NoteDetails, NoteExecutionHint, NoteExecutionMode, NoteFile,
+        NoteMetadata, NoteTag, NoteType,
+    },
+    transaction::{
+        OutputNote, PaymentTransactionData, SwapTransactionData, TransactionRequestBuilder,
+    },
+    utils::Deserializable,
+};
+use miden_lib::{
+    account::wallets::create_basic_wallet,
+    note::{create_p2id_note, utils::build_p2id_recipient},
+};
+mod util;
+
+use crate::util::{
+    FaucetFixture, NOTE_TIMEOUT, SubmitError, account_nonce, assert_balance_delta,
+    assert_client_error, assert_consumed_notes, assert_output_notes, assert_same_tip,
+    assert_submit_rejected, assert_vault, await_block_height, await_note_committed,
+    await_note_consumed, await_note_on_chain, await_nullifier, balance, build_p2idr_recipient,
+    consume_and_forward, consume_notes, create_faucet, create_updatable_wallet,
+    created_note_details, emit_note, import_notes, is_missing_key_error, is_p2id_target_mismatch,
+    is_spent_note_error, mint_and_consume, mint_note, mixed_consume_request, new_falcon_auth,
+    new_mismatched_falcon_auth, pending_tx_count, prove_swap, read_note_file, reimport_note,
+    send_p2id, setup_swap_parties, snapshot, spawn_wallets, swap_note_assets, swap_party,
+    with_harness, write_note_file,
+};
+
+/// Use case of the note tagged for network execution.
+const NETWORK_NOTE_USE_CASE: u16 = 124;
+
+#[tokio::test]
+async fn test_reclaim_p2idr_note() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        // Bob never comes online, he only needs an id to be paid to
+        let bob_authenticator = harness.keystore("bob");
+        let (bob, _) = create_basic_wallet(
+            random(),
+            new_falcon_auth(&bob_authenticator),
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice sends 30 tokens to Bob in a P2IDR note she can reclaim a few blocks later.
+        // --------------------------------------------------------------------------------
+        let tip = alice_client.sync_state().await.unwrap().block_num.as_u32();
+        let reclaim_height = tip + 5;
+        let serial_num = alice_client.rng().draw_word();
+        let p2idr_note = Note::new(
+            NoteAssets::new(vec![
+                FungibleAsset::new(faucet_account.id(), 30).unwrap().into(),
+            ])
+            .unwrap(),
+            NoteMetadata::new(
+                alice.id(),
+                NoteType::Public,
+                NoteTag::from_account_id(bob.id()),
+                NoteExecutionHint::Always,
+                Felt::new(0),
+            )
+            .unwrap(),
+            build_p2idr_recipient(bob.id(), reclaim_height, serial_num).unwrap(),
+        );
+
+        let send_request = TransactionRequestBuilder::new()
+            .with_own_output_notes(vec![OutputNote::Full(p2idr_note.clone())])
+            .build()
+            .unwrap();
+        let send_tx_result = alice_client
+            .new_transaction(alice.id(), send_request)
+            .await
+            .unwrap();
+        alice_client
+            .submit_transaction(send_tx_result)
+            .await
+            .unwrap();
+
+        let note_id = await_note_on_chain(&mut alice_client, p2idr_note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Bob never claims it, so once the reclaim height passes, Alice takes it back.
+        // --------------------------------------------------------------------------------
+        await_block_height(&mut alice_client, reclaim_height, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let reclaim_request = TransactionRequestBuilder::new()
+            .build_consume_notes(vec![note_id])
+            .unwrap();
+        let reclaim_tx_result = alice_client
+            .new_transaction(alice.id(), reclaim_request)
+            .await
+            .unwrap();
+        alice_client
+            .submit_transaction(reclaim_tx_result)
+            .await
+            .unwrap();
+
+        let alice_account = alice_client.get_account(alice.id()).await.unwrap().unwrap();
+        let alice_balance = alice_account
+            .account()
+            .vault()
+            .get_balance(faucet_account.id())
+            .unwrap();
+        assert_eq!(
+            alice_balance, 100,
+            "Alice should have her 30 tokens back after reclaiming"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_send_p2id() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+        let mut bob_client = harness
+            .client("bob", Arc::new(bob_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let (bob, bob_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+        bob_client
+            .add_account(&bob, Some(bob_seed), false)
+            .await
+            .unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice sends 30 tokens to Bob, who consumes the note.
+        // --------------------------------------------------------------------------------
+        let asset = FungibleAsset::new(faucet_account.id(), 30).unwrap().into();
+        let note = assert_balance_delta(
+            &mut alice_client,
+            alice.id(),
+            faucet_account.id(),
+            -30,
+            async |client| {
+                send_p2id(client, alice.id(), bob.id(), asset, NoteType::Public)
+                    .await
+                    .unwrap()
+            },
+        )
+        .await;
+
+        // Rule out Bob's client lagging behind Alice's before he looks for her note
+        assert_same_tip(&mut alice_client, &mut bob_client).await;
+
+        assert_balance_delta(
+            &mut bob_client,
+            bob.id(),
+            faucet_account.id(),
+            30,
+            async |client| {
+                let note_id = await_note_on_chain(client, note.id(), NOTE_TIMEOUT)
+                    .await
+                    .unwrap();
+                consume_notes(client, bob.id(), vec![note_id])
+                    .await
+                    .unwrap();
+            },
+        )
+        .await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_consume_notes_from_two_faucets() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator.clone()))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let mut faucet_ids = Vec::new();
+        for symbol in ["A", "B"] {
+            let (faucet, seed) = create_faucet(
+                TokenSymbol::try_from(symbol).unwrap(),
+                2,
+                Felt::new(1_000),
+                AccountStorageMode::Public,
+                new_falcon_auth(&faucet_authenticator),
+            )
+            .unwrap();
+            faucet_client
+                .add_account(&faucet, Some(seed), false)
+                .await
+                .unwrap();
+            faucet_ids.push(faucet.id());
+        }
+        let (faucet_a, faucet_b) = (faucet_ids[0], faucet_ids[1]);
+
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // One note from each faucet, consumed by Alice in a single transaction.
+        // --------------------------------------------------------------------------------
+        let note_a = mint_note(
+            &mut faucet_client,
+            faucet_a,
+            alice.id(),
+            10,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        let note_b = mint_note(
+            &mut faucet_client,
+            faucet_b,
+            alice.id(),
+            20,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+
+        let note_a = await_note_on_chain(&mut alice_client, note_a.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        let note_b = await_note_on_chain(&mut alice_client, note_b.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        let consume_tx_result = consume_notes(&mut alice_client, alice.id(), vec![note_a, note_b])
+            .await
+            .unwrap();
+        assert_consumed_notes(&consume_tx_result, &[note_a, note_b]);
+
+        // Both notes are spent together, so both nullifiers land in the same block
+        for consumed_note in consume_tx_result.consumed_notes().iter() {
+            await_nullifier(
+                &mut alice_client,
+                consumed_note.note().nullifier(),
+                NOTE_TIMEOUT,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_vault(
+            &mut alice_client,
+            alice.id(),
+            &[(faucet_a, 10), (faucet_b, 20)],
+        )
+        .await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_pay_two_receivers() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        // Bob and Carol only need ids to be paid to
+        let others_authenticator = harness.keystore("bob");
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let mut receivers = Vec::new();
+        for _ in 0..2 {
+            let (receiver, _) = create_basic_wallet(
+                random(),
+                new_falcon_auth(&others_authenticator),
+                AccountType::RegularAccountImmutableCode,
+                AccountStorageMode::Public,
+            )
+            .unwrap();
+            receivers.push(receiver.id());
+        }
+        let (bob, carol) = (receivers[0], receivers[1]);
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice pays Bob and Carol in a single transaction, the way a settlement would.
+        // --------------------------------------------------------------------------------
+        let mut output_notes = Vec::new();
+        for (receiver_id, amount) in [(bob, 10), (carol, 20)] {
+            let note = create_p2id_note(
+                alice.id(),
+                receiver_id,
+                vec![
+                    FungibleAsset::new(faucet_account.id(), amount)
+                        .unwrap()
+                        .into(),
+                ],
+                NoteType::Public,
+                Felt::new(0),
+                alice_client.rng(),
+            )
+            .unwrap();
+            output_notes.push(OutputNote::Full(note));
+        }
+        let pay_request = TransactionRequestBuilder::new()
+            .with_own_output_notes(output_notes)
+            .build()
+            .unwrap();
+        let pay_tx_result = alice_client
+            .new_transaction(alice.id(), pay_request)
+            .await
+            .unwrap();
+
+        assert_output_notes(&pay_tx_result, &[(bob, 10), (carol, 20)]);
+
+        let before = snapshot(&mut alice_client).await.unwrap();
+        alice_client
+            .submit_transaction(pay_tx_result)
+            .await
+            .unwrap();
+        let after = snapshot(&mut alice_client).await.unwrap();
+
+        println!("Alice's client after the payment:\n{after}");
+        println!("Changes:\n{}", before.diff(&after));
+        assert_eq!(
+            after.accounts[&alice.id()].nonce,
+            before.accounts[&alice.id()].nonce + 1
+        );
+        assert_eq!(
+            after.accounts[&alice.id()].balances[&faucet_account.id()],
+            100 - 10 - 20
+        );
+        assert_eq!(after.output_notes.len(), before.output_notes.len() + 2);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_setup_swap_parties() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator.clone()))
+            .await
+            .unwrap();
+
+        let mut faucet_ids = Vec::new();
+        for symbol in ["A", "B"] {
+            let (faucet, seed) = create_faucet(
+                TokenSymbol::try_from(symbol).unwrap(),
+                2,
+                Felt::new(1_000),
+                AccountStorageMode::Public,
+                new_falcon_auth(&faucet_authenticator),
+            )
+            .unwrap();
+            faucet_client
+                .add_account(&faucet, Some(seed), false)
+                .await
+                .unwrap();
+            faucet_ids.push(faucet.id());
+        }
+        let (faucet_a, faucet_b) = (faucet_ids[0], faucet_ids[1]);
+
+        let (mut alice_client, alice, mut bob_client, bob) =
+            setup_swap_parties(&mut harness, &mut faucet_client, faucet_a, faucet_b, 40, 60)
+                .await
+                .unwrap();
+
+        // Each party only holds the token it brings to the swap
+        assert_vault(&mut alice_client, alice, &[(faucet_a, 40)]).await;
+        assert_vault(&mut bob_client, bob, &[(faucet_b, 60)]).await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_updatable_code_wallet() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("UP").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) =
+            create_updatable_wallet(AccountStorageMode::Public, auth_scheme_alice).unwrap();
+        assert_eq!(
+            alice.id().account_type(),
+            AccountType::RegularAccountUpdatableCode
+        );
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        // The updatable wallet runs the same wallet procedures as the immutable one. Adding a
+        // component after deployment isn't covered, since no transaction can change account code.
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            30,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            balance(&mut alice_client, alice.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            30
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_consume_and_forward() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("FW").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let (bob, _) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        let incoming_note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            50,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        let incoming_note_id =
+            await_note_on_chain(&mut alice_client, incoming_note.id(), NOTE_TIMEOUT)
+                .await
+                .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice consumes the incoming note and forwards part of it to Bob in one transaction.
+        // --------------------------------------------------------------------------------
+        let forward_tx_result = consume_and_forward(
+            &mut alice_client,
+            alice.id(),
+            incoming_note_id,
+            bob.id(),
+            FungibleAsset::new(faucet_account.id(), 30).unwrap().into(),
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+
+        let consumed_ids: Vec<_> = forward_tx_result
+            .consumed_notes()
+            .iter()
+            .map(|note| note.id())
+            .collect();
+        assert_eq!(consumed_ids, vec![incoming_note_id]);
+        assert_output_notes(&forward_tx_result, &[(bob.id(), 30)]);
+        assert_eq!(
+            balance(&mut alice_client, alice.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            20
+        );
+
+        let forwarded_note_id = forward_tx_result.created_notes().get_note(0).id();
+        await_note_on_chain(&mut alice_client, forwarded_note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_import_private_note_details() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+        let mut bob_client = harness
+            .client("bob", Arc::new(bob_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("PD").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let (bob, bob_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+        bob_client
+            .add_account(&bob, Some(bob_seed), false)
+            .await
+            .unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice pays Bob in a private note, whose details only reach Bob out of band.
+        // --------------------------------------------------------------------------------
+        let after_block_num = alice_client.get_sync_height().await.unwrap();
+        let payment = PaymentTransactionData::new(
+            vec![FungibleAsset::new(faucet_account.id(), 30).unwrap().into()],
+            alice.id(),
+            bob.id(),
+        );
+        let pay_request = TransactionRequestBuilder::new()
+            .build_pay_to_id(payment, None, NoteType::Private, alice_client.rng())
+            .unwrap();
+        let pay_tx_result = alice_client
+            .new_transaction(alice.id(), pay_request)
+            .await
+            .unwrap();
+        let note_id = pay_tx_result.created_notes().get_note(0).id();
+        let serialized_details = created_note_details(&pay_tx_result, note_id)
+            .expect("Alice's transaction should have the full details of the note");
+        alice_client
+            .submit_transaction(pay_tx_result)
+            .await
+            .unwrap();
+
+        let details = NoteDetails::read_from_bytes(&serialized_details).unwrap();
+        assert_eq!(details.id(), note_id);
+        bob_client
+            .import_note(NoteFile::NoteDetails {
+                details,
+                after_block_num,
+                tag: Some(NoteTag::from_account_id(bob.id())),
+            })
+            .await
+            .unwrap();
+
+        // Bob's client matches the imported details against the chain once the note is committed
+        await_note_committed(&mut bob_client, note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut bob_client, bob.id(), vec![note_id])
+            .await
+            .unwrap();
+        assert_eq!(
+            balance(&mut bob_client, bob.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            30
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_fund_parties_with_unequal_decimals() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator.clone()))
+            .await
+            .unwrap();
+
+        let mut faucet_ids = Vec::new();
+        for (symbol, decimals) in [("TWO", 2), ("SIX", 6)] {
+            let (faucet, seed) = create_faucet(
+                TokenSymbol::try_from(symbol).unwrap(),
+                decimals,
+                Felt::new(1_000_000_000),
+                AccountStorageMode::Public,
+                new_falcon_auth(&faucet_authenticator),
+            )
+            .unwrap();
+            faucet_client
+                .add_account(&faucet, Some(seed), false)
+                .await
+                .unwrap();
+            faucet_ids.push(faucet.id());
+        }
+        let (faucet_a, faucet_b) = (faucet_ids[0], faucet_ids[1]);
+
+        // --------------------------------------------------------------------------------
+        // 1.50 TWO and 1.500000 SIX. Amounts are always raw base units: decimals only matter
+        // for display, so nothing is scaled on the way into the vaults.
+        // --------------------------------------------------------------------------------
+        let (mut alice_client, alice, mut bob_client, bob) = setup_swap_parties(
+            &mut harness,
+            &mut faucet_client,
+            faucet_a,
+            faucet_b,
+            150,
+            1_500_000,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            balance(&mut alice_client, alice, faucet_a).await.unwrap(),
+            150
+        );
+        assert_eq!(
+            balance(&mut bob_client, bob, faucet_b).await.unwrap(),
+            1_500_000
+        );
+
+        // --------------------------------------------------------------------------------
+        // The parties exchange their whole balances through P2ID notes. The raw amounts
+        // arrive unchanged despite the different decimals.
+        // --------------------------------------------------------------------------------
+        let asset_a = FungibleAsset::new(faucet_a, 150).unwrap().into();
+        let note_a = send_p2id(&mut alice_client, alice, bob, asset_a, NoteType::Public)
+            .await
+            .unwrap();
+        let asset_b = FungibleAsset::new(faucet_b, 1_500_000).unwrap().into();
+        let note_b = send_p2id(&mut bob_client, bob, alice, asset_b, NoteType::Public)
+            .await
+            .unwrap();
+
+        let note_a = await_note_on_chain(&mut bob_client, note_a.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut bob_client, bob, vec![note_a])
+            .await
+            .unwrap();
+        let note_b = await_note_on_chain(&mut alice_client, note_b.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice, vec![note_b])
+            .await
+            .unwrap();
+
+        assert_vault(&mut alice_client, alice, &[(faucet_b, 1_500_000)]).await;
+        assert_vault(&mut bob_client, bob, &[(faucet_a, 150)]).await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_consume_p2id_note_of_another_account() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+        let mut bob_client = harness
+            .client("bob", Arc::new(bob_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("WT").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let (bob, bob_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+        bob_client
+            .add_account(&bob, Some(bob_seed), false)
+            .await
+            .unwrap();
+
+        let note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            40,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Bob can import Alice's public note, but P2ID only releases it to Alice.
+        // --------------------------------------------------------------------------------
+        let note_id = await_note_on_chain(&mut bob_client, note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        assert_client_error(
+            consume_notes(&mut bob_client, bob.id(), vec![note_id]).await,
+            is_p2id_target_mismatch,
+        );
+        assert_eq!(
+            balance(&mut bob_client, bob.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            0
+        );
+
+        // The failed attempt never reached the chain, so the note is still there for Alice
+        await_note_on_chain(&mut alice_client, note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice.id(), vec![note_id])
+            .await
+            .unwrap();
+        assert_eq!(
+            balance(&mut alice_client, alice.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            40
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_import_note_file() {
+    with_harness(|mut harness| async move {
+        let note_path = Path::new("note_file_test.note");
+        if note_path.exists() {
+            fs::remove_file(note_path).unwrap();
+        }
+
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut bob_client = harness
+            .client("bob", Arc::new(bob_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NF").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (bob, bob_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        bob_client
+            .add_account(&bob, Some(bob_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // The faucet mints Bob a private note and hands him its details as a file.
+        // --------------------------------------------------------------------------------
+        let after_block_num = faucet_client.get_sync_height().await.unwrap();
+        let note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            bob.id(),
+            60,
+            NoteType::Private,
+        )
+        .await
+        .unwrap();
+
+        write_note_file(
+            note_path,
+            &NoteFile::NoteDetails {
+                details: NoteDetails::from(note.clone()),
+                after_block_num,
+                tag: Some(note.metadata().tag()),
+            },
+        )
+        .unwrap();
+        let note_file = read_note_file(note_path).unwrap();
+        fs::remove_file(note_path).unwrap();
+
+        let NoteFile::NoteDetails { details, .. } = &note_file else {
+            panic!("the note file should round-trip as note details");
+        };
+        assert_eq!(details.id(), note.id());
+
+        // --------------------------------------------------------------------------------
+        // Bob imports the file and consumes the note once it is committed.
+        // --------------------------------------------------------------------------------
+        let note_id = bob_client.import_note(note_file).await.unwrap();
+        assert_eq!(note_id, note.id());
+        await_note_committed(&mut bob_client, note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut bob_client, bob.id(), vec![note_id])
+            .await
+            .unwrap();
+        assert_eq!(
+            balance(&mut bob_client, bob.id(), faucet_account.id())
+                .await
+                .unwrap(),
+            60
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_vault_with_three_faucets() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator.clone()))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice receives a different amount from each faucet, one note at a time.
+        // --------------------------------------------------------------------------------
+        let mut expected = Vec::new();
+        for (symbol, amount) in [("A", 10), ("B", 20), ("C", 30)] {
+            let (faucet, seed) = create_faucet(
+                TokenSymbol::try_from(symbol).unwrap(),
+                2,
+                Felt::new(1_000),
+                AccountStorageMode::Public,
+                new_falcon_auth(&faucet_authenticator),
+            )
+            .unwrap();
+            faucet_client
+                .add_account(&faucet, Some(seed), false)
+                .await
+                .unwrap();
+
+            mint_and_consume(
+                &mut faucet_client,
+                faucet.id(),
+                &mut alice_client,
+                alice.id(),
+                amount,
+            )
+            .await
+            .unwrap();
+            expected.push((faucet.id(), amount));
+
+            // Every new asset type must leave the ones already in the vault untouched
+            assert_vault(&mut alice_client, alice.id(), &expected).await;
+        }
+
+        for &(faucet_id, amount) in &expected {
+            assert_eq!(
+                balance(&mut alice_client, alice.id(), faucet_id)
+                    .await
+                    .unwrap(),
+                amount
+            );
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_consume_mixed_notes() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("MX", 1_000).await.unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice imports the first note once it is on chain, but only holds the second one in full.
+        // --------------------------------------------------------------------------------
+        let confirmed_note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            15,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        let (mut imported, failed) =
+            import_notes(&mut alice_client, &[confirmed_note.id()], NOTE_TIMEOUT).await;
+        assert!(failed.is_empty(), "failed to import the note: {failed:?}");
+
+        let unauthenticated_note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            25,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+
+        let consume_request =
+            mixed_consume_request(vec![imported.remove(0)], vec![unauthenticated_note.clone()])
+                .unwrap();
+        let consume_tx_result = alice_client
+            .new_transaction(alice.id(), consume_request)
+            .await
+            .unwrap();
+        assert_consumed_notes(
+            &consume_tx_result,
+            &[confirmed_note.id(), unauthenticated_note.id()],
+        );
+
+        alice_client
+            .submit_transaction(consume_tx_result)
+            .await
+            .unwrap();
+
+        await_note_consumed(&mut alice_client, confirmed_note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        assert_vault(&mut alice_client, alice.id(), &[(faucet_account.id(), 40)]).await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_falcon_key_mismatch() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        // Alice's keystore gets a key, just not the one her account is built with
+        let auth_scheme_alice = new_mismatched_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("KM").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+
+        assert_client_error(
+            mint_and_consume(
+                &mut faucet_client,
+                faucet_account.id(),
+                &mut alice_client,
+                alice.id(),
+                20,
+            )
+            .await,
+            is_missing_key_error,
+        );
+        assert_eq!(
+            account_nonce(&mut alice_client, alice.id()).await.unwrap(),
+            0
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_consume_spent_unauthenticated_note() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("SU", 1_000).await.unwrap();
+
+        let note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            30,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        let note_id = await_note_on_chain(&mut alice_client, note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice.id(), vec![note_id])
+            .await
+            .unwrap();
+        await_note_consumed(&mut alice_client, note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Consuming the same note again as an unauthenticated note must be refused.
+        // --------------------------------------------------------------------------------
+        // The client executes it fine, only the node knows the note's nullifier is spent
+        let request = mixed_consume_request(vec![], vec![note]).unwrap();
+        let tx_result = alice_client
+            .new_transaction(alice.id(), request)
+            .await
+            .unwrap();
+        let rejection = assert_submit_rejected(&mut alice_client, tx_result).await;
+        assert!(
+            matches!(rejection, SubmitError::NoteAlreadyConsumed),
+            "expected the spent note to be rejected, got: {rejection:?}"
+        );
+        assert_vault(&mut alice_client, alice.id(), &[(faucet_account.id(), 30)]).await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_swap_parties_in_one_shot() {
+    with_harness(|mut harness| async move {
+        let (mut alice_client, alice, faucet_a) =
+            swap_party(&mut harness, "alice", "A", 100).await.unwrap();
+        let (mut bob_client, bob, faucet_b) =
+            swap_party(&mut harness, "bob", "B", 200).await.unwrap();
+        assert_vault(&mut alice_client, alice, &[(faucet_a, 100)]).await;
+        assert_vault(&mut bob_client, bob, &[(faucet_b, 200)]).await;
+
+        // --------------------------------------------------------------------------------
+        // Each party pays the other its whole balance, completing an A/B exchange.
+        // --------------------------------------------------------------------------------
+        let asset_a = FungibleAsset::new(faucet_a, 100).unwrap().into();
+        let note_a = send_p2id(&mut alice_client, alice, bob, asset_a, NoteType::Public)
+            .await
+            .unwrap();
+        let asset_b = FungibleAsset::new(faucet_b, 200).unwrap().into();
+        let note_b = send_p2id(&mut bob_client, bob, alice, asset_b, NoteType::Public)
+            .await
+            .unwrap();
+
+        let note_a = await_note_on_chain(&mut bob_client, note_a.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut bob_client, bob, vec![note_a])
+            .await
+            .unwrap();
+        let note_b = await_note_on_chain(&mut alice_client, note_b.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice, vec![note_b])
+            .await
+            .unwrap();
+
+        assert_vault(&mut alice_client, alice, &[(faucet_b, 200)]).await;
+        assert_vault(&mut bob_client, bob, &[(faucet_a, 100)]).await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_prove_swap() {
+    with_harness(|mut harness| async move {
+        let (mut alice_client, alice, faucet_a) =
+            swap_party(&mut harness, "alice", "A", 100).await.unwrap();
+        let (_bob_client, _bob, faucet_b) =
+            swap_party(&mut harness, "bob", "B", 200).await.unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Alice offers 10 A for 20 B. The swap is proven but never reaches the node.
+        // --------------------------------------------------------------------------------
+        let swap_data = SwapTransactionData::new(
+            alice,
+            FungibleAsset::new(faucet_a, 10).unwrap().into(),
+            FungibleAsset::new(faucet_b, 20).unwrap().into(),
+        );
+        let (tx_result, proven_tx) = prove_swap(&mut alice_client, alice, &swap_data)
+            .await
+            .unwrap();
+
+        assert_eq!(proven_tx.id(), tx_result.executed_transaction().id());
+        assert_eq!(proven_tx.account_id(), alice);
+        assert_eq!(proven_tx.output_notes().num_notes(), 1);
+
+        assert_eq!(pending_tx_count(&mut alice_client).await.unwrap(), 0);
+        assert_vault(&mut alice_client, alice, &[(faucet_a, 100)]).await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_swap_note_assets() {
+    with_harness(|mut harness| async move {
+        let (mut alice_client, alice, faucet_a) =
+            swap_party(&mut harness, "alice", "A", 100).await.unwrap();
+        let (_bob_client, _bob, faucet_b) =
+            swap_party(&mut harness, "bob", "B", 200).await.unwrap();
+
+        let offered_asset: Asset = FungibleAsset::new(faucet_a, 10).unwrap().into();
+        let requested_asset: Asset = FungibleAsset::new(faucet_b, 20).unwrap().into();
+        let swap_data = SwapTransactionData::new(alice, offered_asset, requested_asset);
+        let (tx_result, _) = prove_swap(&mut alice_client, alice, &swap_data)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // The SWAP note locks Alice's 10 A and asks for 20 B in its inputs.
+        // --------------------------------------------------------------------------------
+        let Some(OutputNote::Full(swap_note)) = tx_result.created_notes().iter().next() else {
+            panic!("the swap transaction should create a full note");
+        };
+        let (locked, requested) = swap_note_assets(swap_note);
+
+        assert_eq!(locked, offered_asset);
+        assert_eq!(locked.unwrap_fungible().amount(), 10);
+        assert_eq!(requested, requested_asset);
+        assert_eq!(requested.unwrap_fungible().amount(), 20);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_transfer_near_max_amount() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+        let mut bob_client = harness
+            .client("bob", Arc::new(bob_authenticator))
+            .await
+            .unwrap();
+
+        let max_amount = FungibleAsset::MAX_AMOUNT;
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("MAX").unwrap(),
+            2,
+            Felt::new(max_amount),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let (bob, bob_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+        bob_client
+            .add_account(&bob, Some(bob_seed), false)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // The asset itself can't represent more than the max amount, in one go or by adding up.
+        // --------------------------------------------------------------------------------
+        assert!(FungibleAsset::new(faucet_account.id(), max_amount + 1).is_err());
+        let max_asset = FungibleAsset::new(faucet_account.id(), max_amount).unwrap();
+        assert!(
+            max_asset
+                .add(FungibleAsset::new(faucet_account.id(), 1).unwrap())
+                .is_err()
+        );
+
+        // --------------------------------------------------------------------------------
+        // Alice receives the faucet's entire supply and passes all but one unit on to Bob.
+        // --------------------------------------------------------------------------------
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            max_amount,
+        )
+        .await
+        .unwrap();
+
+        let asset = FungibleAsset::new(faucet_account.id(), max_amount - 1)
+            .unwrap()
+            .into();
+        let note = send_p2id(
+            &mut alice_client,
+            alice.id(),
+            bob.id(),
+            asset,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        let note_id = await_note_on_chain(&mut bob_client, note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut bob_client, bob.id(), vec![note_id])
+            .await
+            .unwrap();
+
+        assert_vault(&mut alice_client, alice.id(), &[(faucet_account.id(), 1)]).await;
+        assert_vault(
+            &mut bob_client,
+            bob.id(),
+            &[(faucet_account.id(), max_amount - 1)],
+        )
+        .await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_reimport_consumed_note() {
+    with_harness(|mut harness| async move {
+        let FaucetFixture {
+            mut faucet_client,
+            faucet_account,
+            mut alice_client,
+            alice,
+        } = harness.faucet_fixture("RI", 1_000).await.unwrap();
+
+        let note = mint_note(
+            &mut faucet_client,
+            faucet_account.id(),
+            alice.id(),
+            50,
+            NoteType::Public,
+        )
+        .await
+        .unwrap();
+        let note_id = await_note_on_chain(&mut alice_client, note.id(), NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut alice_client, alice.id(), vec![note_id])
+            .await
+            .unwrap();
+        await_note_consumed(&mut alice_client, note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // Importing the spent note again neither fails nor makes it consumable again.
+        // --------------------------------------------------------------------------------
+        let record = reimport_note(&mut alice_client, note_id).await.unwrap();
+        assert!(
+            record.is_consumed(),
+            "re-importing must not reset a consumed note"
+        );
+
+        assert_client_error(
+            consume_notes(&mut alice_client, alice.id(), vec![note_id]).await,
+            is_spent_note_error,
+        );
+        assert_vault(&mut alice_client, alice.id(), &[(faucet_account.id(), 50)]).await;
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_spawn_wallets() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("SW").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+
+        let mut wallets =
+            spawn_wallets(&mut harness, 8, &mut faucet_client, faucet_account.id(), 25)
+                .await
+                .unwrap();
+        assert_eq!(wallets.len(), 8);
+
+        for (client, wallet_id) in &mut wallets {
+            assert_vault(client, *wallet_id, &[(faucet_account.id(), 25)]).await;
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_network_execution_note() {
+    with_harness(|mut harness| async move {
+        let faucet_authenticator = harness.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = harness.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+        let bob_authenticator = harness.keystore("bob");
+        let auth_scheme_bob = new_falcon_auth(&bob_authenticator);
+
+        let mut faucet_client = harness
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await
+            .unwrap();
+        let mut alice_client = harness
+            .client("alice", Arc::new(alice_authenticator))
+            .await
+            .unwrap();
+        let mut bob_client = harness
+            .client("bob", Arc::new(bob_authenticator))
+            .await
+            .unwrap();
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from("NW").unwrap(),
+            2,
+            Felt::new(1_000),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )
+        .unwrap();
+        let (alice, alice_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+        let (bob, bob_seed) = create_basic_wallet(
+            random(),
+            auth_scheme_bob,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )
+        .unwrap();
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await
+            .unwrap();
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await
+            .unwrap();
+        bob_client
+            .add_account(&bob, Some(bob_seed), false)
+            .await
+            .unwrap();
+
+        mint_and_consume(
+            &mut faucet_client,
+            faucet_account.id(),
+            &mut alice_client,
+            alice.id(),
+            100,
+        )
+        .await
+        .unwrap();
+
+        // --------------------------------------------------------------------------------
+        // The execution mode lives in the tag. Network notes must be public, since the
+        // network can only execute notes it knows in full.
+        // --------------------------------------------------------------------------------
+        let network_tag =
+            NoteTag::for_public_use_case(NETWORK_NOTE_USE_CASE, 0, NoteExecutionMode::Network)
+                .unwrap();
+        let local_tag =
+            NoteTag::for_public_use_case(NETWORK_NOTE_USE_CASE, 0, NoteExecutionMode::Local)
+                .unwrap();
+        assert_eq!(network_tag.execution_mode(), NoteExecutionMode::Network);
+        assert_eq!(local_tag.execution_mode(), NoteExecutionMode::Local);
+
+        let network_metadata = |note_type| {
+            NoteMetadata::new(
+                alice.id(),
+                note_type,
+                network_tag,
+                NoteExecutionHint::Always,
+                Felt::new(0),
+            )
+        };
+        assert!(
+            network_metadata(NoteType::Private).is_err(),
+            "a private note must not be tagged for network execution"
+        );
+
+        // --------------------------------------------------------------------------------
+        // Alice pays Bob in a P2ID note tagged for network execution. The local node runs no
+        // network transactions for it, so the note simply waits on chain for Bob.
+        // --------------------------------------------------------------------------------
+        let note = Note::new(
+            NoteAssets::new(vec![
+                FungibleAsset::new(faucet_account.id(), 40).unwrap().into(),
+            ])
+            .unwrap(),
+            network_metadata(NoteType::Public).unwrap(),
+            build_p2id_recipient(bob.id(), alice_client.rng().draw_word()).unwrap(),
+        );
+        let request = TransactionRequestBuilder::new()
+            .with_own_output_notes(vec![OutputNote::Full(note.clone())])
+            .build()
+            .unwrap();
+        let note_id = emit_note(&mut alice_client, alice.id(), request)
+            .await
+            .unwrap();
+        assert_eq!(note_id, note.id());
+
+        // The tag, and with it the execution mode, comes back from the node unchanged
+        let record = alice_client.get_input_note(note_id).await.unwrap().unwrap();
+        assert_eq!(
+            record.metadata().unwrap().tag().execution_mode(),
+            NoteExecutionMode::Network
+        );
+
+        await_note_on_chain(&mut bob_client, note_id, NOTE_TIMEOUT)
+            .await
+            .unwrap();
+        consume_notes(&mut bob_client, bob.id(), vec![note_id])
+            .await
+            .unwrap();
+        assert_vault(&mut bob_client, bob.id(), &[(faucet_account.id(), 40)]).await;
+    })
+    .await;
+}
@@ -1,32 +1,171 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt, iter,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use futures::future::try_join_all;
+use miden_assembly::{Assembler, Library};
 use miden_client::{
-    ExecutionOptions, Word,
+    ClientError, ExecutionOptions, Word,
+    auth::AuthSecretKey,
     crypto::FeltRng,
+    keystore::FilesystemKeyStore,
     note::{
-        Note, NoteAssets, NoteExecutionHint, NoteExecutionMode, NoteInputs, NoteMetadata,
-        NoteRecipient, NoteScript, NoteTag, NoteType,
+        Note, NoteAssets, NoteDetails, NoteExecutionHint, NoteExecutionMode, NoteFile, NoteId,
+        NoteInputs, NoteMetadata, NoteRecipient, NoteScript, NoteTag, NoteType, Nullifier,
+    },
+    store::{InputNoteRecord, NoteFilter, TransactionFilter},
+    transaction::{
+        InputNote, OutputNote, PaymentTransactionData, ProvenTransaction, SwapTransactionData,
+        TransactionId, TransactionRequest, TransactionRequestBuilder, TransactionRequestError,
+        TransactionResult, TransactionScript,
+    },
+};
+use miden_lib::{
+    AuthScheme,
+    account::{
+        auth::RpoFalcon512, faucets::create_basic_fungible_faucet, wallets::create_basic_wallet,
+    },
+    errors::{
+        MasmError, note_script_errors::ERR_P2ID_TARGET_ACCT_MISMATCH,
+        tx_kernel_errors::ERR_FUNGIBLE_ASSET_DISTRIBUTE_WOULD_CAUSE_MAX_SUPPLY_TO_BE_EXCEEDED,
+    },
+    note::{create_p2id_note, utils::build_p2id_recipient, well_known_note::WellKnownNote},
+    transaction::TransactionKernel,
+};
+use miden_objects::{
+    AccountError, AssetError, Felt, NoteError,
+    account::{
+        Account, AccountBuilder, AccountComponent, AccountId, AccountStorageMode, AccountType,
+        StorageSlot,
     },
-    transaction::{OutputNote, TransactionRequestBuilder},
+    asset::{Asset, FungibleAsset, TokenSymbol},
+    block::{BlockHeader, BlockNumber},
+    crypto::dsa::rpo_falcon512,
+    utils::{Deserializable, Serializable},
 };
-use miden_lib::{note::utils::build_p2id_recipient, transaction::TransactionKernel};
-use miden_objects::{Felt, account::AccountId, asset::Asset};
-use miden_tx::utils::word_to_masm_push_string;
+use miden_processor::ExecutionError;
+use miden_tx::{AuthenticationError, TransactionExecutorError, utils::word_to_masm_push_string};
+use rand::rngs::StdRng;
 
 use {
     miden_client::{
         Client,
         crypto::RpoRandomCoin,
-        rpc::{Endpoint, TonicRpcClient},
+        rpc::{Endpoint, NodeRpcClient, RpcError, TonicRpcClient},
         store::sqlite_store::SqliteStore,
     },
     miden_tx::auth::TransactionAuthenticator,
-    std::{fs, path::Path, sync::Arc},
+    std::{fs, io, path::Path, sync::Arc},
 };
 
+/// How long to wait for a submitted note to show up on chain.
+pub const NOTE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Amount the drain note makes the faucet distribute to the receiver.
+pub const DRAIN_AMOUNT: u64 = 250;
+/// `aux` value of the note distributed by the drained faucet.
+pub const DRAIN_AUX: u64 = 27;
+
+/// Public use case of the default drain note tag.
+const DRAIN_NOTE_USE_CASE: u16 = 123;
+/// Public use case of the tag of burn-only notes.
+const BURN_NOTE_USE_CASE: u16 = 125;
+
+/// MASM burning the single asset of the executing note through the basic fungible faucet.
+const BURN_NOTE_ASSET: &str = "
+            dropw
+
+            # pad the stack before call
+            padw padw padw padw
+            # => [pad(16)]
+
+            exec.::miden::note::get_assets drop
+            mem_loadw
+            # => [ASSET, pad(12)]
+            call.::miden::contracts::faucets::basic_fungible::burn
+            dropw dropw dropw dropw";
+
+/// Transaction script which does nothing, used to get a transaction into a block.
+const NOOP_TX_SCRIPT: &str = "
+    begin
+        push.0
+        drop
+    end
+";
+
+/// Reserved storage slot of fungible faucets, holding the total token issuance.
+pub const FAUCET_SYSDATA_SLOT: u8 = 0;
+/// Position of the total issuance within the faucet sysdata word.
+pub const ISSUANCE_ELEMENT_INDEX: usize = 3;
+/// Storage slot of the basic fungible faucet component, after the reserved slot and the
+/// Falcon public key. Holds `[max_supply, decimals, token_symbol, 0]`.
+const FAUCET_METADATA_SLOT: u8 = 2;
+
+/// Faucet component patched against the drain: it only re-exports `burn` from the basic
+/// fungible faucet, and wraps `distribute` so that it refuses to run while the transaction
+/// consumes notes. A note script can therefore never have the faucet distribute on its behalf.
+const HARDENED_FAUCET_COMPONENT: &str = "
+    use.miden::tx
+    use.miden::contracts::faucets::basic_fungible
+
+    export.distribute
+        exec.tx::get_input_notes_commitment
+        # => [INPUT_NOTES_COMMITMENT, amount, tag, aux, note_type, execution_hint, RECIPIENT, ...]
+        padw eqw assert.err=\"distribute can't be called while consuming notes\"
+        dropw dropw
+
+        exec.basic_fungible::distribute
+    end
+
+    export.basic_fungible::burn
+";
+
+/// Error of the guard in the `distribute` of [`HARDENED_FAUCET_COMPONENT`]. The message must
+/// match the one of its `assert.err`.
+const ERR_HARDENED_DISTRIBUTE_WHILE_CONSUMING_NOTES: MasmError =
+    MasmError::from_static_str("distribute can't be called while consuming notes");
+
+/// Path of the `distribute` procedure of the basic fungible faucet.
+const BASIC_FUNGIBLE_DISTRIBUTE: &str = "::miden::contracts::faucets::basic_fungible::distribute";
+
 pub trait DrainFaucet {
+    /// Builds a request emitting the drain note, tagged with `tag` in its metadata.
+    ///
+    /// `tag` only controls how the drain note itself is discovered, e.g. by whoever will consume
+    /// it against the faucet. The note the faucet distributes is always tagged for
+    /// `receiver_id`, since that tag is pushed by the drain note script. The two can therefore
+    /// differ.
+    ///
+    /// With a `reclaim_height`, `receiver_id` can take the locked asset back by consuming the
+    /// note itself once the chain reaches that height, e.g. if the drain never happens.
     fn drain_faucet(
         &self,
         receiver_id: AccountId,
         asset_to_burn: Asset,
+        tag: NoteTag,
+        reclaim_height: Option<u32>,
+    ) -> TransactionRequestBuilder;
+
+    /// Builds a request emitting a note which, when consumed against the faucet, only burns
+    /// `asset_to_burn` and doesn't distribute anything in return.
+    fn burn_only(&self, sender_id: AccountId, asset_to_burn: Asset) -> TransactionRequestBuilder;
+
+    /// Builds a request emitting a drain note which, when consumed against the faucet, burns
+    /// `asset_to_burn` and has the faucet distribute a P2ID note to each of `recipients`.
+    ///
+    /// The distributed notes all use the default serial number, so every recipient must be a
+    /// distinct account.
+    fn drain_to_many(
+        &self,
+        sender_id: AccountId,
+        asset_to_burn: Asset,
+        recipients: Vec<(AccountId, u64)>,
     ) -> TransactionRequestBuilder;
 }
 
@@ -35,45 +174,151 @@ impl DrainFaucet for TransactionRequestBuilder {
         &self,
         receiver_id: AccountId,
         asset_to_burn: Asset,
+        tag: NoteTag,
+        reclaim_height: Option<u32>,
+    ) -> TransactionRequestBuilder {
+        let note = get_faucet_drain_note(
+            receiver_id,
+            asset_to_burn,
+            tag,
+            reclaim_height,
+            BASIC_FUNGIBLE_DISTRIBUTE,
+        );
+
+        Self::new().with_own_output_notes(vec![OutputNote::Full(note)])
+    }
+
+    fn burn_only(&self, sender_id: AccountId, asset_to_burn: Asset) -> TransactionRequestBuilder {
+        let note = get_faucet_burn_note(sender_id, asset_to_burn);
+
+        Self::new().with_own_output_notes(vec![OutputNote::Full(note)])
+    }
+
+    fn drain_to_many(
+        &self,
+        sender_id: AccountId,
+        asset_to_burn: Asset,
+        recipients: Vec<(AccountId, u64)>,
     ) -> TransactionRequestBuilder {
-        let note = get_faucet_drain_note(receiver_id, asset_to_burn);
+        let note = get_faucet_drain_to_many_note(sender_id, asset_to_burn, &recipients);
 
         Self::new().with_own_output_notes(vec![OutputNote::Full(note)])
     }
 }
 
-fn get_faucet_drain_note(receiver_id: AccountId, asset_to_burn: Asset) -> Note {
-    let mut rng = RpoRandomCoin::new([Felt::new(1), Felt::new(2), Felt::new(3), Felt::new(4)]);
+/// Returns the transaction kernel assembler, in debug mode, shared by all scripts of the tests.
+///
+/// Building the assembler loads the kernel library, which is slow enough to matter when done for
+/// every script. Clones of the shared instance are cheap.
+pub fn test_assembler() -> &'static Assembler {
+    static ASSEMBLER: OnceLock<Assembler> = OnceLock::new();
+    ASSEMBLER.get_or_init(|| TransactionKernel::assembler().with_debug_mode(true))
+}
 
-    let recipient = build_p2id_recipient(receiver_id, Word::default()).unwrap();
+/// Tag used for the drain note when the caller doesn't need a specific one.
+pub fn default_drain_note_tag() -> NoteTag {
+    NoteTag::for_public_use_case(DRAIN_NOTE_USE_CASE, 0, NoteExecutionMode::Local).unwrap()
+}
 
-    let note_type = NoteType::Public;
-    let note_execution_hint = NoteExecutionHint::Always;
-    let aux = Felt::new(27);
-    let tag = NoteTag::from_account_id(receiver_id);
-    let amount = Felt::new(250);
+/// Tag of burn-only notes, kept apart from the drain note tag so that the two kinds of notes are
+/// never discovered as one another.
+pub fn burn_note_tag() -> NoteTag {
+    NoteTag::for_public_use_case(BURN_NOTE_USE_CASE, 0, NoteExecutionMode::Local).unwrap()
+}
 
-    println!(
-        "recipient_digest: {:?}",
-        word_to_masm_push_string(&recipient.digest())
+/// Builds the drain note, having the faucet distribute through `distribute_procedure`, given by
+/// path or MAST root.
+fn get_faucet_drain_note(
+    receiver_id: AccountId,
+    asset_to_burn: Asset,
+    note_tag: NoteTag,
+    reclaim_height: Option<u32>,
+    distribute_procedure: &str,
+) -> Note {
+    let drain = format!(
+        "
+            # burn the asset
+            {BURN_NOTE_ASSET}
+{distribute}",
+        distribute = distribute_masm_via(receiver_id, DRAIN_AMOUNT, distribute_procedure),
     );
 
-    let note_script = format!(
-        "
-        # burn the asset
+    let note_script = match reclaim_height {
+        None => format!("begin{drain}\n        end"),
+        Some(reclaim_height) => format!(
+            "
         begin
-            dropw
+            # the receiver can reclaim the asset once the reclaim height is reached
+            exec.::miden::account::get_id
+            # => [account_id_prefix, account_id_suffix, NOTE_ARGS]
+            push.{receiver_prefix} eq
+            swap push.{receiver_suffix} eq
+            and
+            # => [is_receiver, NOTE_ARGS]
+            exec.::miden::tx::get_block_number
+            push.{reclaim_height} gte
+            and
+            # => [is_reclaim, NOTE_ARGS]
 
-            # pad the stack before call
-            padw padw padw padw
-            # => [pad(16)]
+            if.true
+                dropw
 
-            exec.::miden::note::get_assets drop
-            mem_loadw
-            # => [ASSET, pad(12)]
-            call.::miden::contracts::faucets::basic_fungible::burn
-            dropw dropw dropw dropw
+                # pad the stack before call
+                padw padw padw padw
+                # => [pad(16)]
+
+                exec.::miden::note::get_assets drop
+                mem_loadw
+                # => [ASSET, pad(12)]
+                call.::miden::contracts::wallets::basic::receive_asset
+                dropw dropw dropw dropw
+            else{drain}
+            end
+        end",
+            receiver_prefix = receiver_id.prefix().as_felt().as_int(),
+            receiver_suffix = receiver_id.suffix().as_int(),
+        ),
+    };
+
+    let note_script = NoteScript::compile(note_script, test_assembler().clone()).unwrap();
+    let serial_num = random_serial_num();
+
+    let faucet_recipient =
+        NoteRecipient::new(serial_num, note_script, NoteInputs::new(vec![]).unwrap());
+
+    let note = Note::new(
+        NoteAssets::new(vec![asset_to_burn]).unwrap(),
+        NoteMetadata::new(
+            receiver_id,
+            NoteType::Public,
+            note_tag,
+            NoteExecutionHint::Always,
+            Felt::new(0),
+        )
+        .unwrap(),
+        faucet_recipient,
+    );
+    note
+}
+
+/// MASM having the faucet distribute `amount` to `receiver_id` in a P2ID note with the default
+/// serial number. Leaves the stack as it found it, so it can be repeated.
+fn distribute_masm(receiver_id: AccountId, amount: u64) -> String {
+    distribute_masm_via(receiver_id, amount, BASIC_FUNGIBLE_DISTRIBUTE)
+}
 
+/// Like [`distribute_masm`], but calls `distribute_procedure`, given by path or MAST root.
+fn distribute_masm_via(receiver_id: AccountId, amount: u64, distribute_procedure: &str) -> String {
+    let recipient = build_p2id_recipient(receiver_id, Word::default()).unwrap();
+
+    let note_type = NoteType::Public;
+    let note_execution_hint = NoteExecutionHint::Always;
+    let aux = Felt::new(DRAIN_AUX);
+    let tag = NoteTag::from_account_id(receiver_id);
+    let amount = Felt::new(amount);
+
+    format!(
+        "
             push.{recipient}
             push.{note_execution_hint}
             push.{note_type}
@@ -82,37 +327,452 @@ fn get_faucet_drain_note(receiver_id: AccountId, asset_to_burn: Asset) -> Note {
             push.{amount}
             # => [amount, tag, aux, note_type, execution_hint, RECIPIENT, pad(7)]
 
-            call.::miden::contracts::faucets::basic_fungible::distribute
+            call.{distribute_procedure}
             # => [note_idx, pad(15)]
 
             # truncate the stack
-            dropw dropw dropw dropw
-        end",
+            dropw dropw dropw dropw",
         note_type = note_type as u8,
         recipient = word_to_masm_push_string(&recipient.digest()),
         note_execution_hint = Felt::from(note_execution_hint),
+    )
+}
+
+fn get_faucet_drain_to_many_note(
+    sender_id: AccountId,
+    asset_to_burn: Asset,
+    recipients: &[(AccountId, u64)],
+) -> Note {
+    let distribute: String = recipients
+        .iter()
+        .map(|&(receiver_id, amount)| distribute_masm(receiver_id, amount))
+        .collect();
+    let note_script = format!(
+        "
+        begin
+            {BURN_NOTE_ASSET}
+{distribute}
+        end"
     );
 
-    let assembler = TransactionKernel::assembler().with_debug_mode(true);
-    let note_script = NoteScript::compile(note_script, assembler).unwrap();
-    let serial_num = rng.draw_word();
+    let note_script = NoteScript::compile(note_script, test_assembler().clone()).unwrap();
+    let serial_num = random_serial_num();
 
     let faucet_recipient =
         NoteRecipient::new(serial_num, note_script, NoteInputs::new(vec![]).unwrap());
 
-    let note = Note::new(
+    Note::new(
         NoteAssets::new(vec![asset_to_burn]).unwrap(),
         NoteMetadata::new(
-            receiver_id,
+            sender_id,
             NoteType::Public,
-            NoteTag::for_public_use_case(123, 0, NoteExecutionMode::Local).unwrap(),
+            default_drain_note_tag(),
             NoteExecutionHint::Always,
             Felt::new(0),
         )
         .unwrap(),
         faucet_recipient,
+    )
+}
+
+fn get_faucet_burn_note(sender_id: AccountId, asset_to_burn: Asset) -> Note {
+    let note_script = format!(
+        "
+        begin
+            {BURN_NOTE_ASSET}
+        end"
     );
-    note
+
+    let note_script = NoteScript::compile(note_script, test_assembler().clone()).unwrap();
+    let serial_num = random_serial_num();
+
+    let faucet_recipient =
+        NoteRecipient::new(serial_num, note_script, NoteInputs::new(vec![]).unwrap());
+
+    Note::new(
+        NoteAssets::new(vec![asset_to_burn]).unwrap(),
+        NoteMetadata::new(
+            sender_id,
+            NoteType::Public,
+            burn_note_tag(),
+            NoteExecutionHint::Always,
+            Felt::new(0),
+        )
+        .unwrap(),
+        faucet_recipient,
+    )
+}
+
+/// Returns a coin seeded from a fresh random seed, so that two coins never draw the same values.
+fn random_coin() -> RpoRandomCoin {
+    RpoRandomCoin::new(rand::random::<[u64; 4]>().map(Felt::new))
+}
+
+/// Draws a serial number from a fresh random seed, so that notes built in one run never collide.
+fn random_serial_num() -> Word {
+    random_coin().draw_word()
+}
+
+/// Compiles the note script in `script_src` with [`test_assembler`], without building a note.
+///
+/// Gives quick feedback while writing MASM: the error carries the assembler's diagnostic.
+pub fn check_note_script(script_src: &str) -> Result<NoteScript, NoteError> {
+    NoteScript::compile(script_src, test_assembler().clone())
+}
+
+/// Builds a note running the MASM in `script_src`, compiled with [`test_assembler`].
+///
+/// The compiled script replaces the one of `recipient`, whose serial number and inputs are kept.
+pub fn custom_note(
+    script_src: &str,
+    assets: Vec<Asset>,
+    recipient: NoteRecipient,
+    metadata: NoteMetadata,
+) -> Result<Note, NoteError> {
+    let script = check_note_script(script_src)?;
+    let recipient = NoteRecipient::new(recipient.serial_num(), script, recipient.inputs().clone());
+
+    Ok(Note::new(NoteAssets::new(assets)?, metadata, recipient))
+}
+
+/// Returns the note the faucet distributes to `receiver_id` when the drain note is consumed.
+pub fn expected_drain_output_note(faucet_id: AccountId, receiver_id: AccountId) -> Note {
+    drain_output_note(faucet_id, receiver_id, DRAIN_AMOUNT)
+}
+
+/// Returns the note distributing `amount` of `faucet_id` to `receiver_id` out of a drain.
+pub fn drain_output_note(faucet_id: AccountId, receiver_id: AccountId, amount: u64) -> Note {
+    Note::new(
+        NoteAssets::new(vec![FungibleAsset::new(faucet_id, amount).unwrap().into()]).unwrap(),
+        NoteMetadata::new(
+            faucet_id,
+            NoteType::Public,
+            NoteTag::from_account_id(receiver_id),
+            NoteExecutionHint::Always,
+            Felt::new(DRAIN_AUX),
+        )
+        .unwrap(),
+        build_p2id_recipient(receiver_id, Word::default()).unwrap(),
+    )
+}
+
+/// Runs the drain exploit of `receiver_id` against `faucet_id`, burning `asset_to_burn`.
+///
+/// `client` must track both the receiver's wallet and the faucet. Returns the note distributed
+/// by the faucet once it is on chain.
+pub async fn run_drain(
+    client: &mut Client,
+    receiver_id: AccountId,
+    faucet_id: AccountId,
+    asset_to_burn: Asset,
+) -> Result<Note, ClientError> {
+    run_drain_with_mode(
+        client,
+        receiver_id,
+        faucet_id,
+        asset_to_burn,
+        TestMode::Full,
+    )
+    .await
+}
+
+/// Like [`run_drain`], but under [`TestMode::ProveOnly`] both steps of the drain are proven and
+/// dropped instead of submitted.
+///
+/// The drain note then never reaches the chain, so the faucet consumes it unauthenticated, as
+/// created by the first step. The returned note is the one the faucet would distribute.
+pub async fn run_drain_with_mode(
+    client: &mut Client,
+    receiver_id: AccountId,
+    faucet_id: AccountId,
+    asset_to_burn: Asset,
+    mode: TestMode,
+) -> Result<Note, ClientError> {
+    // First, the receiver emits a note that locks the asset to burn.
+    let malicious_note_request = TransactionRequestBuilder::new()
+        .drain_faucet(receiver_id, asset_to_burn, default_drain_note_tag(), None)
+        .build()?;
+    let tx_result = client
+        .new_transaction(receiver_id, malicious_note_request)
+        .await?;
+    let drain_note = match tx_result.created_notes().iter().next() {
+        Some(OutputNote::Full(note)) => note.clone(),
+        _ => panic!("drain transaction should create a full note"),
+    };
+    finish_transaction(client, tx_result, mode).await?;
+
+    // Then the drain note is consumed against the faucet.
+    let consume_request = match mode {
+        TestMode::Full => {
+            let drain_note_id = await_note_on_chain(client, drain_note.id(), NOTE_TIMEOUT).await?;
+            faucet_consume_request(drain_note_id)?
+        }
+        TestMode::ProveOnly => faucet_consume_unauthenticated_request(drain_note)?,
+    };
+    let tx_result = client.new_transaction(faucet_id, consume_request).await?;
+    finish_transaction(client, tx_result, mode).await?;
+
+    let output_note = expected_drain_output_note(faucet_id, receiver_id);
+    if mode == TestMode::Full {
+        await_note_on_chain(client, output_note.id(), NOTE_TIMEOUT).await?;
+    }
+
+    Ok(output_note)
+}
+
+/// Transaction script of faucets consuming notes. It does nothing, the notes do all the work.
+fn faucet_consume_script() -> TransactionScript {
+    TransactionScript::compile("begin\npush.1\ndrop\nend", test_assembler().clone()).unwrap()
+}
+
+/// Builds the request consuming the note with `note_id` against a faucet.
+pub fn faucet_consume_request(
+    note_id: NoteId,
+) -> Result<TransactionRequest, TransactionRequestError> {
+    TransactionRequestBuilder::new()
+        .with_custom_script(faucet_consume_script())
+        .build_consume_notes(vec![note_id])
+}
+
+/// Like [`faucet_consume_request`], but for a `note` which isn't on chain, consumed
+/// unauthenticated.
+pub fn faucet_consume_unauthenticated_request(
+    note: Note,
+) -> Result<TransactionRequest, TransactionRequestError> {
+    TransactionRequestBuilder::new()
+        .with_custom_script(faucet_consume_script())
+        .with_unauthenticated_input_notes([(note, None)])
+        .build()
+}
+
+/// How far the helpers taking a mode, [`prove_and_submit_with_mode`] and
+/// [`run_drain_with_mode`], take their transactions.
+///
+/// Flows of several transactions hand the notes of one step to the next unauthenticated when
+/// nothing is submitted, so they can be proven end to end without touching the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TestMode {
+    /// Prove the transaction and submit it to the node.
+    #[default]
+    Full,
+    /// Prove the transaction locally and stop there, leaving the node and the client's store
+    /// untouched.
+    ProveOnly,
+}
+
+/// Executes `request` as `account_id`, then proves and submits the transaction.
+///
+/// For tests which don't need to inspect the transaction in between. Returns the id of the
+/// submitted transaction.
+pub async fn prove_and_submit(
+    client: &mut Client,
+    account_id: AccountId,
+    request: TransactionRequest,
+) -> Result<TransactionId, ClientError> {
+    prove_and_submit_with_mode(client, account_id, request, TestMode::Full).await
+}
+
+/// Like [`prove_and_submit`], but under [`TestMode::ProveOnly`] the proven transaction is dropped
+/// instead of submitted.
+pub async fn prove_and_submit_with_mode(
+    client: &mut Client,
+    account_id: AccountId,
+    request: TransactionRequest,
+    mode: TestMode,
+) -> Result<TransactionId, ClientError> {
+    let tx_result = client.new_transaction(account_id, request).await?;
+    let tx_id = tx_result.executed_transaction().id();
+    finish_transaction(client, tx_result, mode).await?;
+
+    Ok(tx_id)
+}
+
+/// Submits the executed `tx_result`, or under [`TestMode::ProveOnly`] only proves it.
+async fn finish_transaction(
+    client: &mut Client,
+    tx_result: TransactionResult,
+    mode: TestMode,
+) -> Result<(), ClientError> {
+    match mode {
+        // Submitting proves the transaction before sending it to the node
+        TestMode::Full => client.submit_transaction(tx_result).await?,
+        TestMode::ProveOnly => {
+            client.testing_prove_transaction(&tx_result).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Executes `request`, which must create exactly one note, as `account_id` and submits it.
+///
+/// Returns the id of the created note once it is on chain.
+pub async fn emit_note(
+    client: &mut Client,
+    account_id: AccountId,
+    request: TransactionRequest,
+) -> Result<NoteId, ClientError> {
+    let tx_result = client.new_transaction(account_id, request).await?;
+    let note_id = tx_result
+        .created_notes()
+        .iter()
+        .next()
+        .expect("transaction should create a note")
+        .id();
+    client.submit_transaction(tx_result).await?;
+
+    await_note_on_chain(client, note_id, NOTE_TIMEOUT).await
+}
+
+/// Consumes the note with `note_id` in a transaction executed against `faucet_id`.
+///
+/// This works without the faucet's key since the `burn` first bumps the nonce, so the epilogue
+/// check of "changing account state -> nonce bumped" is satisfied. Returns the submitted
+/// transaction.
+pub async fn consume_against_faucet(
+    client: &mut Client,
+    faucet_id: AccountId,
+    note_id: NoteId,
+) -> Result<TransactionResult, ClientError> {
+    let request = faucet_consume_request(note_id)?;
+    let tx_result = client.new_transaction(faucet_id, request).await?;
+    client.submit_transaction(tx_result.clone()).await?;
+
+    Ok(tx_result)
+}
+
+/// Names and cleans up the SQLite stores and keystores of a single test.
+///
+/// Names carry a random suffix, so tests running in parallel never share a file. Every store and
+/// keystore handed out by the harness is removed when it is dropped, including when the test
+/// panics.
+pub struct TestHarness {
+    name: String,
+    stores: Vec<String>,
+    keystores: Vec<String>,
+}
+
+impl TestHarness {
+    pub fn new() -> Self {
+        Self {
+            name: format!("harness_{:08x}", rand::random::<u32>()),
+            stores: Vec::new(),
+            keystores: Vec::new(),
+        }
+    }
+
+    /// Returns the name of the store of `party`, which is removed along with the harness.
+    pub fn store_name(&mut self, party: &str) -> String {
+        let db_filename = format!("{}_{party}_store.sqlite3", self.name);
+        self.stores.push(db_filename.clone());
+        db_filename
+    }
+
+    /// Opens the keystore of `party`, which is removed along with the harness.
+    pub fn keystore(&mut self, party: &str) -> FilesystemKeyStore<StdRng> {
+        let name = format!("{}_{party}", self.name);
+        let keystore = named_keystore(&name);
+        self.keystores.push(name);
+        keystore
+    }
+
+    /// Sets up a client for `party` on its own store, signing with `authenticator`.
+    pub async fn client<T: TransactionAuthenticator + 'static>(
+        &mut self,
+        party: &str,
+        authenticator: Arc<T>,
+    ) -> Result<Client, Box<dyn std::error::Error>> {
+        let db_filename = self.store_name(party);
+        setup_client(authenticator, &db_filename).await
+    }
+
+    /// Sets up a [`FaucetFixture`] with a faucet for `symbol` with 2 decimals and a max supply of
+    /// `max_supply`.
+    ///
+    /// The faucet and Alice are the parties `faucet` and `alice` of the harness, so Alice's
+    /// keystore never holds the faucet key.
+    pub async fn faucet_fixture(
+        &mut self,
+        symbol: &str,
+        max_supply: u64,
+    ) -> Result<FaucetFixture, Box<dyn std::error::Error>> {
+        let faucet_authenticator = self.keystore("faucet");
+        let auth_scheme_faucet = new_falcon_auth(&faucet_authenticator);
+        let alice_authenticator = self.keystore("alice");
+        let auth_scheme_alice = new_falcon_auth(&alice_authenticator);
+
+        let mut faucet_client = self
+            .client("faucet", Arc::new(faucet_authenticator))
+            .await?;
+        let mut alice_client = self.client("alice", Arc::new(alice_authenticator)).await?;
+
+        let (faucet_account, faucet_seed) = create_faucet(
+            TokenSymbol::try_from(symbol)?,
+            2,
+            Felt::new(max_supply),
+            AccountStorageMode::Public,
+            auth_scheme_faucet,
+        )?;
+        let (alice, alice_seed) = create_basic_wallet(
+            rand::random(),
+            auth_scheme_alice,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )?;
+
+        faucet_client
+            .add_account(&faucet_account, Some(faucet_seed), false)
+            .await?;
+        alice_client
+            .add_account(&alice, Some(alice_seed), false)
+            .await?;
+
+        Ok(FaucetFixture {
+            faucet_client,
+            faucet_account,
+            alice_client,
+            alice,
+        })
+    }
+}
+
+/// A public faucet and Alice's public wallet, each tracked by a client of its own, as set up by
+/// [`TestHarness::faucet_fixture`].
+pub struct FaucetFixture {
+    pub faucet_client: Client,
+    pub faucet_account: Account,
+    pub alice_client: Client,
+    pub alice: Account,
+}
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        for db_filename in &self.stores {
+            remove_store(db_filename);
+        }
+        for name in &self.keystores {
+            remove_keystore(name);
+        }
+    }
+}
+
+/// Runs `f` against a fresh [`TestHarness`].
+///
+/// The harness moves into the body, so its stores are torn down once the body completes or
+/// unwinds. Prefer this over [`reset_store`], whose fixed store names collide when tests run in
+/// parallel.
+pub async fn with_harness<F, Fut>(f: F) -> Fut::Output
+where
+    F: FnOnce(TestHarness) -> Fut,
+    Fut: Future,
+{
+    f(TestHarness::new()).await
 }
 
 /// Removes the test SQLite store file if it exists.
@@ -126,9 +786,66 @@ pub async fn reset_store() {
     ];
 
     for filename in &db_files {
-        let path = Path::new(filename);
-        if path.exists() {
-            fs::remove_file(path).unwrap();
+        remove_store(filename);
+    }
+}
+
+/// Removes a single SQLite store file if it exists.
+///
+/// Tests running in parallel with their own store names use this instead of [`reset_store`].
+pub fn remove_store(db_filename: &str) {
+    let path = Path::new(db_filename);
+    if path.exists() {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+/// Removes the store `{name}_store.sqlite3` and the keystore directory `keystore/{name}`, if
+/// they exist.
+///
+/// Unlike [`reset_store`], this only touches the files of a single named test, so it is safe to
+/// call while other tests run in parallel.
+pub fn reset_named(name: &str) {
+    remove_store(&format!("{name}_store.sqlite3"));
+    remove_keystore(name);
+}
+
+/// Removes the keystore directory `keystore/{name}` if it exists.
+fn remove_keystore(name: &str) {
+    let keystore_dir = Path::new("keystore").join(name);
+    if keystore_dir.exists() {
+        fs::remove_dir_all(keystore_dir).unwrap();
+    }
+}
+
+/// Opens the keystore directory `keystore/{name}`, the one [`reset_named`] removes.
+///
+/// Tests get their keystores from [`TestHarness::keystore`] instead, which names and removes them
+/// on its own.
+pub fn named_keystore(name: &str) -> FilesystemKeyStore<StdRng> {
+    FilesystemKeyStore::new(Path::new("keystore").join(name)).unwrap()
+}
+
+/// Options controlling how [`setup_client_with_options`] initializes a client.
+pub struct SetupOptions {
+    /// Whether to sync the client with the node right after creating it.
+    pub sync_on_setup: bool,
+    /// Whether a failed initial sync fails the setup. When unset the error is ignored.
+    pub propagate_sync_error: bool,
+    /// RPC endpoint of the node. Defaults to the local node.
+    pub endpoint: Endpoint,
+    /// RPC client to use instead of connecting to `endpoint`, e.g. a fake node. When set,
+    /// `endpoint` is ignored.
+    pub rpc_api: Option<Arc<dyn NodeRpcClient + Send>>,
+}
+
+impl Default for SetupOptions {
+    fn default() -> Self {
+        Self {
+            sync_on_setup: true,
+            propagate_sync_error: false,
+            endpoint: Endpoint::localhost(),
+            rpc_api: None,
         }
     }
 }
@@ -136,16 +853,28 @@ pub async fn reset_store() {
 pub async fn setup_client<T: TransactionAuthenticator + 'static>(
     authenticator: Arc<T>,
     db_filename: &str,
+) -> Result<Client, Box<dyn std::error::Error>> {
+    setup_client_with_options(authenticator, db_filename, SetupOptions::default()).await
+}
+
+pub async fn setup_client_with_options<T: TransactionAuthenticator + 'static>(
+    authenticator: Arc<T>,
+    db_filename: &str,
+    options: SetupOptions,
 ) -> Result<Client, Box<dyn std::error::Error>> {
     let sqlite_store = SqliteStore::new(db_filename.into()).await?;
     let store = Arc::new(sqlite_store);
 
-    let rng = RpoRandomCoin::new(Default::default());
+    // Each client gets its own seed, otherwise two clients would draw the same serial numbers
+    // for the notes they create.
+    let rng = random_coin();
 
-    let endpoint = Endpoint::localhost();
+    let rpc_api = options
+        .rpc_api
+        .unwrap_or_else(|| Arc::new(TonicRpcClient::new(&options.endpoint, 100)));
 
     let mut client = Client::new(
-        Arc::new(TonicRpcClient::new(&endpoint, 100)),
+        rpc_api,
         Box::new(rng),
         store,
         authenticator,
@@ -154,7 +883,1601 @@ pub async fn setup_client<T: TransactionAuthenticator + 'static>(
         None,
     );
 
-    let _ = client.sync_state().await;
+    if options.sync_on_setup {
+        let sync_result = client.sync_state().await;
+        if options.propagate_sync_error {
+            sync_result?;
+        }
+    }
 
     Ok(client)
 }
+
+/// Asserts `result` failed with an error matching `predicate`, e.g. [`is_max_supply_exceeded`],
+/// and returns the error.
+///
+/// Panics with the actual outcome otherwise, so a failing test shows what went wrong instead of
+/// a bare unwrap.
+#[track_caller]
+pub fn assert_client_error<T: fmt::Debug>(
+    result: Result<T, ClientError>,
+    predicate: impl Fn(&ClientError) -> bool,
+) -> ClientError {
+    match result {
+        Ok(value) => panic!("expected an error, but the call succeeded with: {value:?}"),
+        Err(err) if predicate(&err) => err,
+        Err(err) => panic!("unexpected error: {err:?}"),
+    }
+}
+
+/// Returns whether `err` means the node could not be reached, e.g. because the client points at
+/// a misconfigured endpoint.
+pub fn is_connection_error(err: &ClientError) -> bool {
+    matches!(err, ClientError::RpcError(RpcError::ConnectionError(_)))
+}
+
+/// Generates a fresh Falcon key, adds it to `keystore` and returns the matching auth scheme.
+pub fn new_falcon_auth(keystore: &FilesystemKeyStore<StdRng>) -> AuthScheme {
+    let secret_key = rpo_falcon512::SecretKey::new();
+    let pub_key = secret_key.public_key();
+    keystore
+        .add_key(&AuthSecretKey::RpoFalcon512(secret_key))
+        .unwrap();
+
+    AuthScheme::RpoFalcon512 { pub_key }
+}
+
+/// Returns an auth scheme for a fresh Falcon key, which isn't stored anywhere.
+///
+/// For accounts which are only built, never tracked by a client nor signed for.
+pub fn throwaway_auth_scheme() -> AuthScheme {
+    AuthScheme::RpoFalcon512 {
+        pub_key: rpo_falcon512::SecretKey::new().public_key(),
+    }
+}
+
+/// Returns whether `keystore` holds the secret key behind `auth_scheme`, i.e. whether a client
+/// using it can sign for accounts built with `auth_scheme`.
+pub fn holds_key(keystore: &FilesystemKeyStore<StdRng>, auth_scheme: &AuthScheme) -> bool {
+    let AuthScheme::RpoFalcon512 { pub_key } = auth_scheme;
+
+    keystore.get_key(Word::from(*pub_key)).unwrap().is_some()
+}
+
+/// Like [`new_falcon_auth`], but the key added to `keystore` doesn't match the returned auth
+/// scheme, as happens when keystores and accounts get crossed.
+///
+/// An account built with the returned auth scheme is tracked fine, but `keystore` can't sign for
+/// it.
+pub fn new_mismatched_falcon_auth(keystore: &FilesystemKeyStore<StdRng>) -> AuthScheme {
+    // The stored key is never used, it only makes the keystore non-empty
+    new_falcon_auth(keystore);
+
+    AuthScheme::RpoFalcon512 {
+        pub_key: rpo_falcon512::SecretKey::new().public_key(),
+    }
+}
+
+/// Creates a basic fungible faucet with a random init seed, returning it with the seed.
+///
+/// Tests go through this rather than [`create_basic_fungible_faucet`] so that every faucet is
+/// built the same way and only the parameters that matter to the test are spelled out.
+pub fn create_faucet(
+    symbol: TokenSymbol,
+    decimals: u8,
+    max_supply: Felt,
+    storage_mode: AccountStorageMode,
+    auth_scheme: AuthScheme,
+) -> Result<(Account, Word), AccountError> {
+    create_basic_fungible_faucet(
+        rand::random(),
+        symbol,
+        decimals,
+        max_supply,
+        storage_mode,
+        auth_scheme,
+    )
+}
+
+/// Creates a basic fungible faucet like [`create_faucet`], but signed by a fresh Falcon key that
+/// isn't added to any keystore.
+///
+/// The key is returned alongside the faucet and its seed, so a test can track the faucet on a
+/// client that can't sign for it and hand the key over later.
+pub fn create_keyless_faucet(
+    symbol: TokenSymbol,
+    decimals: u8,
+    max_supply: Felt,
+    storage_mode: AccountStorageMode,
+) -> Result<(Account, Word, AuthSecretKey), AccountError> {
+    let secret_key = rpo_falcon512::SecretKey::new();
+    let auth_scheme = AuthScheme::RpoFalcon512 {
+        pub_key: secret_key.public_key(),
+    };
+    let (faucet, seed) = create_faucet(symbol, decimals, max_supply, storage_mode, auth_scheme)?;
+
+    Ok((faucet, seed, AuthSecretKey::RpoFalcon512(secret_key)))
+}
+
+/// Creates a fungible faucet whose faucet logic is `faucet_component` instead of the basic fungible
+/// faucet, with a random init seed, returning it with the seed.
+pub fn create_faucet_with_component(
+    faucet_component: AccountComponent,
+    storage_mode: AccountStorageMode,
+    auth_scheme: AuthScheme,
+) -> Result<(Account, Word), AccountError> {
+    let AuthScheme::RpoFalcon512 { pub_key } = auth_scheme;
+
+    AccountBuilder::new(rand::random())
+        .account_type(AccountType::FungibleFaucet)
+        .storage_mode(storage_mode)
+        .with_auth_component(RpoFalcon512::new(pub_key))
+        .with_component(faucet_component)
+        .build()
+}
+
+/// Builds the patched faucet component of [`HARDENED_FAUCET_COMPONENT`], with the same storage as
+/// the basic fungible faucet.
+pub fn hardened_faucet_component(
+    symbol: TokenSymbol,
+    decimals: u8,
+    max_supply: Felt,
+) -> AccountComponent {
+    let metadata = [
+        max_supply,
+        Felt::from(decimals),
+        symbol.into(),
+        Felt::new(0),
+    ];
+
+    AccountComponent::compile(
+        HARDENED_FAUCET_COMPONENT,
+        test_assembler().clone(),
+        vec![StorageSlot::Value(metadata)],
+    )
+    .unwrap()
+    .with_supported_type(AccountType::FungibleFaucet)
+}
+
+/// Builds the request minting `amount` to `receiver_id` through the `distribute` procedure of
+/// `faucet_component`, for faucets which don't expose the basic one.
+///
+/// The minted note is the one returned by [`drain_output_note`] for the same receiver and amount.
+pub fn component_mint_request(
+    faucet_component: &AccountComponent,
+    receiver_id: AccountId,
+    amount: u64,
+) -> Result<TransactionRequest, TransactionRequestError> {
+    let distribute_root = procedure_root(faucet_component.library(), "distribute");
+    let mint_script = format!(
+        "begin{distribute}\n        end",
+        distribute = distribute_masm_via(receiver_id, amount, &distribute_root),
+    );
+
+    TransactionRequestBuilder::new()
+        .with_custom_script(
+            TransactionScript::compile(mint_script, test_assembler().clone()).unwrap(),
+        )
+        .with_expected_output_recipients(vec![
+            build_p2id_recipient(receiver_id, Word::default()).unwrap(),
+        ])
+        .build()
+}
+
+/// Builds the request emitting a drain note of `receiver_id` aimed at a faucet built from
+/// `faucet_component`, calling that component's own `distribute` procedure.
+///
+/// The drain note of [`DrainFaucet::drain_faucet`] calls the basic fungible faucet's
+/// `distribute`, which a faucet wrapping that procedure doesn't export.
+pub fn component_drain_request(
+    faucet_component: &AccountComponent,
+    receiver_id: AccountId,
+    asset_to_burn: Asset,
+) -> Result<TransactionRequest, TransactionRequestError> {
+    let distribute_root = procedure_root(faucet_component.library(), "distribute");
+    let note = get_faucet_drain_note(
+        receiver_id,
+        asset_to_burn,
+        default_drain_note_tag(),
+        None,
+        &distribute_root,
+    );
+
+    TransactionRequestBuilder::new()
+        .with_own_output_notes(vec![OutputNote::Full(note)])
+        .build()
+}
+
+/// Returns the MAST root of the procedure `name` exported by `library`, formatted for a `call`.
+fn procedure_root(library: &Library, name: &str) -> String {
+    let export = library
+        .exports()
+        .find(|export| export.name.as_str() == name)
+        .unwrap_or_else(|| panic!("library doesn't export `{name}`"));
+    let node_id = library.get_export_node_id(export);
+
+    library.mast_forest()[node_id].digest().to_hex()
+}
+
+/// Creates a basic wallet whose code can be updated, with a random init seed, returning it with
+/// the seed.
+///
+/// Only the account type differs from an immutable wallet: the protocol has no transaction that
+/// changes an account's code, so the code of this wallet can't actually be updated yet.
+pub fn create_updatable_wallet(
+    storage_mode: AccountStorageMode,
+    auth_scheme: AuthScheme,
+) -> Result<(Account, Word), AccountError> {
+    create_basic_wallet(
+        rand::random(),
+        auth_scheme,
+        AccountType::RegularAccountUpdatableCode,
+        storage_mode,
+    )
+}
+
+/// Error returned by [`create_faucet_from_symbol`].
+#[derive(Debug)]
+pub enum FaucetSetupError {
+    /// The symbol is not a valid token symbol: one to six uppercase ASCII letters.
+    InvalidSymbol { symbol: String, reason: String },
+    /// The faucet account itself could not be built.
+    Account(AccountError),
+}
+
+impl From<AccountError> for FaucetSetupError {
+    fn from(err: AccountError) -> Self {
+        Self::Account(err)
+    }
+}
+
+/// Like [`create_faucet`], but parses `symbol` first so an invalid symbol is reported as
+/// [`FaucetSetupError::InvalidSymbol`] instead of a panic at the call site.
+pub fn create_faucet_from_symbol(
+    symbol: &str,
+    decimals: u8,
+    max_supply: Felt,
+    storage_mode: AccountStorageMode,
+    auth_scheme: AuthScheme,
+) -> Result<(Account, Word), FaucetSetupError> {
+    let token_symbol =
+        TokenSymbol::try_from(symbol).map_err(|err| FaucetSetupError::InvalidSymbol {
+            symbol: symbol.to_string(),
+            reason: err.to_string(),
+        })?;
+
+    Ok(create_faucet(
+        token_symbol,
+        decimals,
+        max_supply,
+        storage_mode,
+        auth_scheme,
+    )?)
+}
+
+/// Imports the note with `note_id` into `client`, polling the node until it is on chain.
+///
+/// Fails with [`ClientError::NoteNotFoundOnChain`] if the note doesn't show up within `timeout`.
+pub async fn await_note_on_chain(
+    client: &mut Client,
+    note_id: NoteId,
+    timeout: Duration,
+) -> Result<NoteId, ClientError> {
+    let start_time = Instant::now();
+
+    while start_time.elapsed() < timeout {
+        match client.import_note(NoteFile::NoteId(note_id)).await {
+            Ok(note_id) => {
+                client.sync_state().await?;
+                return Ok(note_id);
+            }
+            Err(ClientError::NoteNotFoundOnChain(_)) => {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                client.sync_state().await?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(ClientError::NoteNotFoundOnChain(note_id))
+}
+
+/// Imports the note with `note_id` into `client` again and returns its record afterwards.
+///
+/// Importing a note the client already tracks doesn't reset it, so the record of a consumed note
+/// stays consumed.
+pub async fn reimport_note(
+    client: &mut Client,
+    note_id: NoteId,
+) -> Result<InputNoteRecord, ClientError> {
+    client.import_note(NoteFile::NoteId(note_id)).await?;
+    client.sync_state().await?;
+
+    client
+        .get_input_note(note_id)
+        .await?
+        .ok_or(ClientError::NoteNotFoundOnChain(note_id))
+}
+
+/// Imports each of `note_ids` into `client` with [`await_note_on_chain`], giving every note its
+/// own `timeout`.
+///
+/// A failing note does not stop the others from being imported: the imported notes and the
+/// failures are returned separately, so a caller can tell exactly which notes went missing.
+pub async fn import_notes(
+    client: &mut Client,
+    note_ids: &[NoteId],
+    timeout: Duration,
+) -> (Vec<InputNote>, Vec<(NoteId, ClientError)>) {
+    let mut imported = Vec::new();
+    let mut failed = Vec::new();
+
+    for &note_id in note_ids {
+        let result = async {
+            await_note_on_chain(client, note_id, timeout).await?;
+            let record = client
+                .get_input_note(note_id)
+                .await?
+                .ok_or(ClientError::NoteNotFoundOnChain(note_id))?;
+            Ok::<_, ClientError>(InputNote::try_from(record)?)
+        }
+        .await;
+
+        match result {
+            Ok(note) => imported.push(note),
+            Err(err) => failed.push((note_id, err)),
+        }
+    }
+
+    (imported, failed)
+}
+
+/// Imports every note created by `tx` into `client` with [`import_notes`], in the order the
+/// transaction created them.
+///
+/// Fails with the error of the first note that couldn't be imported.
+pub async fn import_created_notes(
+    client: &mut Client,
+    tx: &TransactionResult,
+    timeout: Duration,
+) -> Result<Vec<InputNote>, ClientError> {
+    let note_ids: Vec<NoteId> = tx.created_notes().iter().map(|note| note.id()).collect();
+    let (imported, failed) = import_notes(client, &note_ids, timeout).await;
+
+    match failed.into_iter().next() {
+        Some((_, err)) => Err(err),
+        None => Ok(imported),
+    }
+}
+
+/// Syncs `client` until the note with `note_id` shows up in its store without importing it.
+///
+/// Only notes matching one of the client's tracked tags are picked up this way. Fails with
+/// [`ClientError::NoteNotFoundOnChain`] if the note isn't discovered within `timeout`.
+pub async fn await_note_discovered(
+    client: &mut Client,
+    note_id: NoteId,
+    timeout: Duration,
+) -> Result<InputNoteRecord, ClientError> {
+    let start_time = Instant::now();
+
+    while start_time.elapsed() < timeout {
+        client.sync_state().await?;
+        if let Some(note) = client.get_input_note(note_id).await? {
+            return Ok(note);
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    Err(ClientError::NoteNotFoundOnChain(note_id))
+}
+
+/// Returns how many of the transactions submitted by `client` are not committed yet.
+pub async fn pending_tx_count(client: &mut Client) -> Result<usize, ClientError> {
+    Ok(client
+        .get_transactions(TransactionFilter::Uncommitted)
+        .await?
+        .len())
+}
+
+/// Syncs `client` until all of its submitted transactions are committed.
+///
+/// Fails with [`ClientError::RecencyConditionError`] if some are still pending after `timeout`.
+pub async fn await_no_pending_txs(
+    client: &mut Client,
+    timeout: Duration,
+) -> Result<(), ClientError> {
+    let start_time = Instant::now();
+
+    loop {
+        client.sync_state().await?;
+        let pending = pending_tx_count(client).await?;
+        if pending == 0 {
+            return Ok(());
+        }
+        if start_time.elapsed() >= timeout {
+            return Err(ClientError::RecencyConditionError(format!(
+                "{pending} transactions still pending after {timeout:?}"
+            )));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Syncs `client` until the tracked note with `note_id` has an inclusion proof, i.e. until the
+/// client sees it committed on chain.
+///
+/// Useful for private notes imported from their details, which the client can only match
+/// against the chain once the note lands in a block. Fails with
+/// [`ClientError::RecencyConditionError`] if the note isn't committed within `timeout`.
+pub async fn await_note_committed(
+    client: &mut Client,
+    note_id: NoteId,
+    timeout: Duration,
+) -> Result<InputNoteRecord, ClientError> {
+    let start_time = Instant::now();
+
+    loop {
+        client.sync_state().await?;
+        let note = client
+            .get_input_note(note_id)
+            .await?
+            .ok_or(ClientError::NoteNotFoundOnChain(note_id))?;
+        if note.inclusion_proof().is_some() {
+            return Ok(note);
+        }
+        if start_time.elapsed() >= timeout {
+            return Err(ClientError::RecencyConditionError(format!(
+                "note {note_id} was not committed within {timeout:?}"
+            )));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Syncs `client` until the nullifier of the note with `note_id` shows up on chain, i.e. until
+/// the client sees the note as consumed.
+///
+/// The note must already be tracked by `client`. Fails with
+/// [`ClientError::RecencyConditionError`] if the note isn't consumed within `timeout`.
+pub async fn await_note_consumed(
+    client: &mut Client,
+    note_id: NoteId,
+    timeout: Duration,
+) -> Result<InputNoteRecord, ClientError> {
+    let start_time = Instant::now();
+
+    loop {
+        client.sync_state().await?;
+        let note = client
+            .get_input_note(note_id)
+            .await?
+            .ok_or(ClientError::NoteNotFoundOnChain(note_id))?;
+        if note.is_consumed() {
+            return Ok(note);
+        }
+        if start_time.elapsed() >= timeout {
+            return Err(ClientError::RecencyConditionError(format!(
+                "note {note_id} was not consumed within {timeout:?}"
+            )));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Polls the node until `nullifier` is recorded in its nullifier set, then syncs `client`.
+///
+/// The node is queried through the client's RPC API rather than the client's store, so
+/// `nullifier` doesn't have to belong to a note the client tracks. Fails with
+/// [`ClientError::RecencyConditionError`] if the nullifier isn't recorded within `timeout`.
+pub async fn await_nullifier(
+    client: &mut Client,
+    nullifier: Nullifier,
+    timeout: Duration,
+) -> Result<(), ClientError> {
+    let start_time = Instant::now();
+
+    loop {
+        let updates = client
+            .test_rpc_api()
+            .check_nullifiers_by_prefix(&[nullifier.prefix()], 0.into())
+            .await?;
+        if updates.iter().any(|update| update.nullifier == nullifier) {
+            client.sync_state().await?;
+            return Ok(());
+        }
+        if start_time.elapsed() >= timeout {
+            return Err(ClientError::RecencyConditionError(format!(
+                "nullifier {nullifier} was not recorded within {timeout:?}"
+            )));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Mints `amount` tokens of `faucet_id` to `target_id` in a note of type `note_type`.
+///
+/// Returns the created note once the transaction is submitted.
+pub async fn mint_note(
+    faucet_client: &mut Client,
+    faucet_id: AccountId,
+    target_id: AccountId,
+    amount: u64,
+    note_type: NoteType,
+) -> Result<Note, ClientError> {
+    let asset = FungibleAsset::new(faucet_id, amount)?;
+    let mint_request = TransactionRequestBuilder::new().build_mint_fungible_asset(
+        asset,
+        target_id,
+        note_type,
+        faucet_client.rng(),
+    )?;
+
+    let tx_result = faucet_client
+        .new_transaction(faucet_id, mint_request)
+        .await?;
+    let note = match tx_result.created_notes().iter().next() {
+        Some(OutputNote::Full(note)) => note.clone(),
+        _ => panic!("mint transaction should create a full note"),
+    };
+    faucet_client.submit_transaction(tx_result).await?;
+
+    Ok(note)
+}
+
+/// Returns the code of the assertion that failed while executing the transaction program, if
+/// that is why `err` happened.
+fn failed_assertion_code(err: &ClientError) -> Option<Felt> {
+    match err {
+        ClientError::TransactionExecutorError(
+            TransactionExecutorError::TransactionProgramExecutionFailed(
+                ExecutionError::FailedAssertion { err_code, .. },
+            ),
+        ) => Some(*err_code),
+        _ => None,
+    }
+}
+
+/// Returns whether `err` is a faucet refusing to mint because the max supply would be exceeded.
+///
+/// The faucet enforces the cap with an assertion in its distribute procedure, so the condition
+/// surfaces as a failed assertion carrying the faucet's error code.
+pub fn is_max_supply_exceeded(err: &ClientError) -> bool {
+    failed_assertion_code(err)
+        == Some(ERR_FUNGIBLE_ASSET_DISTRIBUTE_WOULD_CAUSE_MAX_SUPPLY_TO_BE_EXCEEDED.code())
+}
+
+/// Returns whether `err` is the faucet of [`hardened_faucet_component`] refusing to distribute
+/// while the transaction consumes notes.
+pub fn is_hardened_distribute_refused(err: &ClientError) -> bool {
+    failed_assertion_code(err) == Some(ERR_HARDENED_DISTRIBUTE_WHILE_CONSUMING_NOTES.code())
+}
+
+/// Returns whether `err` is a transaction failing to be signed because the client's keystore
+/// doesn't hold the account's key.
+///
+/// The authenticator is called by the transaction host while the program runs, so its
+/// [`AuthenticationError`] is one of the sources of the executor error.
+pub fn is_missing_key_error(err: &ClientError) -> bool {
+    let ClientError::TransactionExecutorError(executor_err) = err else {
+        return false;
+    };
+
+    iter::successors(Some(executor_err as &dyn Error), |err| err.source()).any(|err| {
+        matches!(
+            err.downcast_ref(),
+            Some(AuthenticationError::UnknownPublicKey(_))
+        )
+    })
+}
+
+/// Returns whether `err` is a P2ID note refusing to be consumed by an account other than its
+/// target.
+///
+/// P2ID checks the consuming account against the receiver encoded in its inputs, so the
+/// condition surfaces as a failed assertion carrying the note script's error code.
+pub fn is_p2id_target_mismatch(err: &ClientError) -> bool {
+    failed_assertion_code(err) == Some(ERR_P2ID_TARGET_ACCT_MISMATCH.code())
+}
+
+/// Mints `amount` tokens of `faucet_id` to `wallet_id` in a public note and has the wallet
+/// consume it.
+pub async fn mint_and_consume(
+    faucet_client: &mut Client,
+    faucet_id: AccountId,
+    wallet_client: &mut Client,
+    wallet_id: AccountId,
+    amount: u64,
+) -> Result<(), ClientError> {
+    let note = mint_note(
+        faucet_client,
+        faucet_id,
+        wallet_id,
+        amount,
+        NoteType::Public,
+    )
+    .await?;
+
+    let note_id = await_note_on_chain(wallet_client, note.id(), NOTE_TIMEOUT).await?;
+    consume_notes(wallet_client, wallet_id, vec![note_id]).await?;
+
+    Ok(())
+}
+
+/// Creates `n` wallets, each on its own client with a store from `harness`, and funds each with
+/// `amount` tokens of `faucet_id`, which `faucet_client` must track.
+///
+/// The wallets' clients are set up concurrently, and so are the consumes of the minted notes.
+/// Only the mints run one after the other, since the faucet's transactions build on each other.
+pub async fn spawn_wallets(
+    harness: &mut TestHarness,
+    n: usize,
+    faucet_client: &mut Client,
+    faucet_id: AccountId,
+    amount: u64,
+) -> Result<Vec<(Client, AccountId)>, Box<dyn std::error::Error>> {
+    let store_names: Vec<_> = (0..n)
+        .map(|i| harness.store_name(&format!("wallet_{i}")))
+        .collect();
+    let keystore = harness.keystore("wallets");
+    let mut wallets = try_join_all(store_names.iter().map(|db_filename| {
+        let keystore = keystore.clone();
+        async move {
+            let auth_scheme = new_falcon_auth(&keystore);
+            let mut client = setup_client(Arc::new(keystore), db_filename).await?;
+
+            let (wallet, seed) = create_basic_wallet(
+                rand::random(),
+                auth_scheme,
+                AccountType::RegularAccountImmutableCode,
+                AccountStorageMode::Public,
+            )?;
+            client.add_account(&wallet, Some(seed), false).await?;
+
+            Ok::<_, Box<dyn std::error::Error>>((client, wallet.id()))
+        }
+    }))
+    .await?;
+
+    let mut notes = Vec::with_capacity(n);
+    for (_, wallet_id) in &wallets {
+        let note = mint_note(
+            faucet_client,
+            faucet_id,
+            *wallet_id,
+            amount,
+            NoteType::Public,
+        )
+        .await?;
+        notes.push(note.id());
+    }
+
+    try_join_all(
+        wallets
+            .iter_mut()
+            .zip(notes)
+            .map(|((client, wallet_id), note_id)| async move {
+                let note_id = await_note_on_chain(client, note_id, NOTE_TIMEOUT).await?;
+                consume_notes(client, *wallet_id, vec![note_id]).await?;
+                await_note_consumed(client, note_id, NOTE_TIMEOUT).await
+            }),
+    )
+    .await?;
+
+    Ok(wallets)
+}
+
+/// Creates Alice and Bob with their own clients and funds them for a swap: Alice gets
+/// `a_amount` of `faucet_a` and Bob gets `b_amount` of `faucet_b`.
+///
+/// Both faucets must be tracked by `faucet_client`. The parties are the `alice` and `bob` parties
+/// of `harness`.
+///
+/// Fails with the first error hit while setting up either party, including its client.
+pub async fn setup_swap_parties(
+    harness: &mut TestHarness,
+    faucet_client: &mut Client,
+    faucet_a: AccountId,
+    faucet_b: AccountId,
+    a_amount: u64,
+    b_amount: u64,
+) -> Result<(Client, AccountId, Client, AccountId), Box<dyn std::error::Error>> {
+    let mut parties = Vec::new();
+
+    for (name, faucet_id, amount) in [("alice", faucet_a, a_amount), ("bob", faucet_b, b_amount)] {
+        let keystore = harness.keystore(name);
+        let auth_scheme = new_falcon_auth(&keystore);
+        let mut client = harness.client(name, Arc::new(keystore)).await?;
+
+        let (wallet, seed) = create_basic_wallet(
+            rand::random(),
+            auth_scheme,
+            AccountType::RegularAccountImmutableCode,
+            AccountStorageMode::Public,
+        )?;
+        client.add_account(&wallet, Some(seed), false).await?;
+
+        mint_and_consume(faucet_client, faucet_id, &mut client, wallet.id(), amount).await?;
+        parties.push((client, wallet.id()));
+    }
+
+    let (bob_client, bob_id) = parties.pop().unwrap();
+    let (alice_client, alice_id) = parties.pop().unwrap();
+
+    Ok((alice_client, alice_id, bob_client, bob_id))
+}
+
+/// Builds a request consuming `authenticated` notes, which the client must already track, together
+/// with `unauthenticated` notes, which the transaction carries in full.
+///
+/// The node verifies the authenticated notes against the notes it already has, while the
+/// unauthenticated ones are only checked once the transaction is submitted.
+pub fn mixed_consume_request(
+    authenticated: Vec<InputNote>,
+    unauthenticated: Vec<Note>,
+) -> Result<TransactionRequest, TransactionRequestError> {
+    TransactionRequestBuilder::new()
+        .with_authenticated_input_notes(authenticated.iter().map(|note| (note.id(), None)))
+        .with_unauthenticated_input_notes(unauthenticated.into_iter().map(|note| (note, None)))
+        .build()
+}
+
+/// Returns whether `err` is the node rejecting a transaction because one of its input notes was
+/// already consumed.
+///
+/// Nullifiers of unauthenticated notes are only checked against the chain once the transaction
+/// is submitted, so the condition surfaces as a failed submit request carrying the node's reason.
+/// Connection failures and malformed responses never match.
+///
+/// The client has no variant or code for this rejection: the node's reason only reaches it as
+/// free text in [`RpcError::RequestError`]. The reason is therefore matched by substring, and a
+/// reworded node message will stop this from matching.
+pub fn is_note_already_consumed(err: &ClientError) -> bool {
+    match err {
+        ClientError::RpcError(RpcError::RequestError(_, reason)) => {
+            let reason = reason.to_lowercase();
+            reason.contains("already consumed") || reason.contains("alreadyconsumed")
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether `err` is a transaction failing because one of its input notes is already
+/// spent, wherever that was caught.
+///
+/// Unlike [`is_note_already_consumed`], this also covers the client refusing a note its store
+/// already marks as consumed, before anything reaches the node.
+pub fn is_spent_note_error(err: &ClientError) -> bool {
+    is_note_already_consumed(err)
+        || matches!(
+            err,
+            ClientError::TransactionRequestError(
+                TransactionRequestError::InputNoteAlreadyConsumed(_)
+            )
+        )
+}
+
+/// Stands up the self-contained swap party `party` of `harness`: a client with its own keystore,
+/// tracking a fresh faucet for `faucet_symbol` and a wallet holding that faucet's whole supply of
+/// `fund_amount`.
+///
+/// Returns the client with the ids of the wallet and the faucet.
+pub async fn swap_party(
+    harness: &mut TestHarness,
+    party: &str,
+    faucet_symbol: &str,
+    fund_amount: u64,
+) -> Result<(Client, AccountId, AccountId), Box<dyn std::error::Error>> {
+    let keystore = harness.keystore(party);
+    let auth_scheme_faucet = new_falcon_auth(&keystore);
+    let auth_scheme_wallet = new_falcon_auth(&keystore);
+    let mut client = harness.client(party, Arc::new(keystore)).await?;
+
+    let (faucet, faucet_seed) = create_faucet(
+        TokenSymbol::try_from(faucet_symbol)?,
+        2,
+        Felt::new(fund_amount),
+        AccountStorageMode::Public,
+        auth_scheme_faucet,
+    )?;
+    let (wallet, wallet_seed) = create_basic_wallet(
+        rand::random(),
+        auth_scheme_wallet,
+        AccountType::RegularAccountImmutableCode,
+        AccountStorageMode::Public,
+    )?;
+    client
+        .add_account(&faucet, Some(faucet_seed), false)
+        .await?;
+    client
+        .add_account(&wallet, Some(wallet_seed), false)
+        .await?;
+
+    let note = mint_note(
+        &mut client,
+        faucet.id(),
+        wallet.id(),
+        fund_amount,
+        NoteType::Public,
+    )
+    .await?;
+    let note_id = await_note_on_chain(&mut client, note.id(), NOTE_TIMEOUT).await?;
+    consume_notes(&mut client, wallet.id(), vec![note_id]).await?;
+
+    Ok((client, wallet.id(), faucet.id()))
+}
+
+/// Why the node rejected a submitted transaction, as categorized by [`assert_submit_rejected`].
+#[derive(Debug)]
+pub enum SubmitError {
+    /// One of the transaction's input notes was already consumed.
+    NoteAlreadyConsumed,
+    /// Any other rejection by the node.
+    Other(ClientError),
+}
+
+/// Submits `tx_result` and asserts the node rejects it, returning the categorized reason.
+///
+/// Proving happens locally before submission, so a failure there fails the assertion: only
+/// rejections coming back from the node count. So does a request that never reached the node,
+/// e.g. a dropped connection.
+pub async fn assert_submit_rejected(
+    client: &mut Client,
+    tx_result: TransactionResult,
+) -> SubmitError {
+    let err = client
+        .submit_transaction(tx_result)
+        .await
+        .expect_err("the node should reject the transaction");
+    assert!(
+        matches!(err, ClientError::RpcError(RpcError::RequestError(..))),
+        "expected the node to reject the transaction, but it failed before that: {err:?}"
+    );
+
+    if is_note_already_consumed(&err) {
+        SubmitError::NoteAlreadyConsumed
+    } else {
+        SubmitError::Other(err)
+    }
+}
+
+/// Has `account_id` consume the notes with `note_ids`, which `client` must already track.
+///
+/// Returns the submitted transaction.
+pub async fn consume_notes(
+    client: &mut Client,
+    account_id: AccountId,
+    note_ids: Vec<NoteId>,
+) -> Result<TransactionResult, ClientError> {
+    let consume_request = TransactionRequestBuilder::new().build_consume_notes(note_ids)?;
+    let tx_result = client.new_transaction(account_id, consume_request).await?;
+    client.submit_transaction(tx_result.clone()).await?;
+
+    Ok(tx_result)
+}
+
+/// Sends `asset` from `sender_id` to `receiver_id` in a pay-to-id note of type `note_type`.
+///
+/// Returns the created note once the transaction is submitted.
+pub async fn send_p2id(
+    sender_client: &mut Client,
+    sender_id: AccountId,
+    receiver_id: AccountId,
+    asset: Asset,
+    note_type: NoteType,
+) -> Result<Note, ClientError> {
+    let payment = PaymentTransactionData::new(vec![asset], sender_id, receiver_id);
+    let send_request = TransactionRequestBuilder::new().build_pay_to_id(
+        payment,
+        None,
+        note_type,
+        sender_client.rng(),
+    )?;
+
+    let tx_result = sender_client
+        .new_transaction(sender_id, send_request)
+        .await?;
+    let note = match tx_result.created_notes().iter().next() {
+        Some(OutputNote::Full(note)) => note.clone(),
+        _ => panic!("pay-to-id transaction should create a full note"),
+    };
+    sender_client.submit_transaction(tx_result).await?;
+
+    Ok(note)
+}
+
+/// Has `account_id` consume the note with `note_id` and, in the same transaction, send `asset`
+/// on to `next_recipient_id` in a pay-to-id note of type `note_type`.
+///
+/// `client` must already track the incoming note. Returns the submitted transaction, whose only
+/// output note is the forwarded one.
+pub async fn consume_and_forward(
+    client: &mut Client,
+    account_id: AccountId,
+    note_id: NoteId,
+    next_recipient_id: AccountId,
+    asset: Asset,
+    note_type: NoteType,
+) -> Result<TransactionResult, ClientError> {
+    let forwarded_note = create_p2id_note(
+        account_id,
+        next_recipient_id,
+        vec![asset],
+        note_type,
+        Felt::new(0),
+        client.rng(),
+    )?;
+    let forward_request = TransactionRequestBuilder::new()
+        .with_authenticated_input_notes([(note_id, None)])
+        .with_own_output_notes(vec![OutputNote::Full(forwarded_note)])
+        .build()?;
+
+    let tx_result = client.new_transaction(account_id, forward_request).await?;
+    client.submit_transaction(tx_result.clone()).await?;
+
+    Ok(tx_result)
+}
+
+/// Returns the type of the note with `note_id` created by `tx`, or `None` if `tx` didn't create
+/// it.
+pub fn created_note_type(tx: &TransactionResult, note_id: NoteId) -> Option<NoteType> {
+    tx.created_notes()
+        .iter()
+        .find(|output_note| output_note.id() == note_id)
+        .map(|output_note| output_note.metadata().note_type())
+}
+
+/// Serializes the details of the note with `note_id` created by `tx`.
+///
+/// This is what the sender of a private note hands to its receiver out of band, to be imported
+/// through [`NoteFile::NoteDetails`]. Returns `None` if `tx` didn't create the note with full
+/// details.
+pub fn created_note_details(tx: &TransactionResult, note_id: NoteId) -> Option<Vec<u8>> {
+    tx.created_notes()
+        .iter()
+        .find_map(|output_note| match output_note {
+            OutputNote::Full(note) if note.id() == note_id => {
+                Some(NoteDetails::from(note.clone()).to_bytes())
+            }
+            _ => None,
+        })
+}
+
+/// Writes `note_file` to `path`, the way a note is handed to another party out of band.
+pub fn write_note_file(path: &Path, note_file: &NoteFile) -> io::Result<()> {
+    fs::write(path, note_file.to_bytes())
+}
+
+/// Reads a note file written by [`write_note_file`] back from `path`.
+pub fn read_note_file(path: &Path) -> io::Result<NoteFile> {
+    let bytes = fs::read(path)?;
+    NoteFile::read_from_bytes(&bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Builds a fungible asset of `faucet_id` from a human-readable `amount`, scaled by the faucet's
+/// `decimals`, e.g. 1.50 with 2 decimals is 150 base units.
+///
+/// The scaled amount is rounded to the nearest base unit, which absorbs the float error of
+/// amounts like 1.15. Panics if `amount` is negative or not finite.
+pub fn fungible_from_decimal(
+    faucet_id: AccountId,
+    amount: f64,
+    decimals: u8,
+) -> Result<FungibleAsset, AssetError> {
+    assert!(
+        amount.is_finite() && amount >= 0.0,
+        "invalid token amount {amount}"
+    );
+    let base_units = (amount * 10f64.powi(decimals.into())).round() as u64;
+
+    FungibleAsset::new(faucet_id, base_units)
+}
+
+/// Builds the data of a swap in which `sender_id` offers `offered_amount` tokens of
+/// `offered_faucet` for `requested_amount` tokens of `requested_faucet`.
+///
+/// Both amounts are human-readable and converted with [`fungible_from_decimal`], each with the
+/// decimals of its own faucet.
+pub fn swap_data_from_decimal(
+    sender_id: AccountId,
+    offered_faucet: AccountId,
+    offered_amount: f64,
+    offered_decimals: u8,
+    requested_faucet: AccountId,
+    requested_amount: f64,
+    requested_decimals: u8,
+) -> Result<SwapTransactionData, AssetError> {
+    let offered_asset = fungible_from_decimal(offered_faucet, offered_amount, offered_decimals)?;
+    let requested_asset =
+        fungible_from_decimal(requested_faucet, requested_amount, requested_decimals)?;
+
+    Ok(SwapTransactionData::new(
+        sender_id,
+        offered_asset.into(),
+        requested_asset.into(),
+    ))
+}
+
+/// Returns the balance of `faucet_id` tokens held by `account_id`, as tracked by `client`.
+pub async fn balance(
+    client: &mut Client,
+    account_id: AccountId,
+    faucet_id: AccountId,
+) -> Result<u64, ClientError> {
+    let account = client
+        .get_account(account_id)
+        .await?
+        .ok_or(ClientError::AccountDataNotFound(account_id))?;
+
+    Ok(account.account().vault().get_balance(faucet_id)?)
+}
+
+/// Asserts the vault of `account_id`, as tracked by `client`, holds exactly the fungible
+/// `(faucet_id, amount)` pairs in `expected`, in any order, and no other asset.
+pub async fn assert_vault(
+    client: &mut Client,
+    account_id: AccountId,
+    expected: &[(AccountId, u64)],
+) {
+    let account = client
+        .get_account(account_id)
+        .await
+        .unwrap()
+        .expect("account should be tracked by the client");
+
+    let mut actual: Vec<(AccountId, u64)> = account
+        .account()
+        .vault()
+        .assets()
+        .map(|asset| match asset {
+            Asset::Fungible(asset) => (asset.faucet_id(), asset.amount()),
+            Asset::NonFungible(asset) => panic!("unexpected non-fungible asset {asset:?}"),
+        })
+        .collect();
+    let mut expected = expected.to_vec();
+
+    actual.sort();
+    expected.sort();
+    assert_eq!(actual, expected, "unexpected vault of account {account_id}");
+}
+
+/// Reads storage slot `slot` of `account_id`, as tracked by `client`.
+pub async fn read_storage_slot(
+    client: &mut Client,
+    account_id: AccountId,
+    slot: u8,
+) -> Result<Word, ClientError> {
+    let account = client
+        .get_account(account_id)
+        .await?
+        .ok_or(ClientError::AccountDataNotFound(account_id))?;
+
+    Ok(account.account().storage().get_item(slot)?)
+}
+
+/// Reads the total issuance of the fungible faucet `faucet_id` as tracked by `client`.
+pub async fn faucet_issuance(
+    client: &mut Client,
+    faucet_id: AccountId,
+) -> Result<u64, ClientError> {
+    let sysdata = read_storage_slot(client, faucet_id, FAUCET_SYSDATA_SLOT).await?;
+
+    Ok(sysdata[ISSUANCE_ELEMENT_INDEX].as_int())
+}
+
+/// Asserts the fungible faucet `faucet_id`, as tracked by `client`, was initialized with the
+/// given symbol, decimals and max supply.
+pub async fn assert_faucet_metadata(
+    client: &mut Client,
+    faucet_id: AccountId,
+    symbol: TokenSymbol,
+    decimals: u8,
+    max_supply: Felt,
+) {
+    let metadata = read_storage_slot(client, faucet_id, FAUCET_METADATA_SLOT)
+        .await
+        .unwrap();
+
+    assert_eq!(metadata[0], max_supply, "unexpected max supply");
+    assert_eq!(metadata[1], Felt::from(decimals), "unexpected decimals");
+    assert_eq!(metadata[2], Felt::from(symbol), "unexpected token symbol");
+}
+
+/// Returns the ids of all faucet accounts tracked by `client`.
+pub async fn tracked_faucets(client: &mut Client) -> Result<Vec<AccountId>, ClientError> {
+    let faucets = client
+        .get_account_headers()
+        .await?
+        .into_iter()
+        .map(|(header, _)| header.id())
+        .filter(|id| {
+            matches!(
+                id.account_type(),
+                AccountType::FungibleFaucet | AccountType::NonFungibleFaucet
+            )
+        })
+        .collect();
+
+    Ok(faucets)
+}
+
+/// Copies the current state of `account_id` from `from`'s store into `to`.
+///
+/// Public accounts can be imported from the node with `import_account_by_id`, but the node only
+/// knows the commitment of private accounts, so their state has to be handed over out-of-band.
+pub async fn share_account_state(
+    from: &mut Client,
+    to: &mut Client,
+    account_id: AccountId,
+) -> Result<(), ClientError> {
+    let record = from
+        .get_account(account_id)
+        .await?
+        .ok_or(ClientError::AccountDataNotFound(account_id))?;
+
+    to.add_account(record.account(), record.seed().copied(), false)
+        .await
+}
+
+/// Syncs `client` until the chain tip reaches block `target`.
+///
+/// Fails with [`ClientError::RecencyConditionError`] if the tip is still behind after `timeout`.
+pub async fn await_block_height(
+    client: &mut Client,
+    target: u32,
+    timeout: Duration,
+) -> Result<(), ClientError> {
+    let start_time = Instant::now();
+
+    loop {
+        let tip = client.sync_state().await?.block_num.as_u32();
+        if tip >= target {
+            return Ok(());
+        }
+        if start_time.elapsed() >= timeout {
+            return Err(ClientError::RecencyConditionError(format!(
+                "chain tip {tip} did not reach block {target} within {timeout:?}"
+            )));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Submits a no-op transaction against `account_id` and waits for the chain tip to move past the
+/// block `client` was synced to.
+///
+/// A quiet local node only has to produce blocks for new transactions, so tests depending on
+/// block heights use this to nudge it along.
+///
+/// The script itself leaves the account untouched. The nonce bump that gives the transaction a
+/// state change comes from the account's auth component, which increments the nonce when it
+/// authenticates the transaction. `client` must therefore hold the key of `account_id`.
+pub async fn force_block(client: &mut Client, account_id: AccountId) -> Result<(), ClientError> {
+    let tip = client.sync_state().await?.block_num.as_u32();
+
+    let noop_request = TransactionRequestBuilder::new()
+        .with_custom_script(
+            TransactionScript::compile(NOOP_TX_SCRIPT, test_assembler().clone()).unwrap(),
+        )
+        .build()?;
+    let tx_result = client.new_transaction(account_id, noop_request).await?;
+    client.submit_transaction(tx_result).await?;
+
+    await_block_height(client, tip + 1, NOTE_TIMEOUT).await
+}
+
+/// Syncs `a` and `b` and asserts they end up at the same chain tip.
+///
+/// A block may land between the two syncs, so the clients get a few rounds to catch up with each
+/// other before they are considered out of step. Once they reach the same height, the commitments
+/// of their tip headers must match too, so a client on a fork or a stale header never passes.
+pub async fn assert_same_tip(a: &mut Client, b: &mut Client) {
+    const MAX_ROUNDS: usize = 5;
+
+    let mut tips = (0, 0);
+    for _ in 0..MAX_ROUNDS {
+        let tip_a = a.sync_state().await.unwrap().block_num;
+        let tip_b = b.sync_state().await.unwrap().block_num;
+        if tip_a == tip_b {
+            assert_eq!(
+                tip_header(a, tip_a).await.commitment(),
+                tip_header(b, tip_b).await.commitment(),
+                "clients disagree on the header of block {tip_a}"
+            );
+            return;
+        }
+        tips = (tip_a.as_u32(), tip_b.as_u32());
+    }
+
+    panic!(
+        "clients did not converge on the same chain tip: {} vs {}",
+        tips.0, tips.1
+    );
+}
+
+/// Returns the header of block `block_num` from the store of `client`, which keeps the header of
+/// the block it last synced to.
+async fn tip_header(client: &mut Client, block_num: BlockNumber) -> BlockHeader {
+    let (header, _) = client
+        .test_store()
+        .get_block_header_by_num(block_num)
+        .await
+        .unwrap()
+        .unwrap_or_else(|| panic!("client has no header for its tip, block {block_num}"));
+
+    header
+}
+
+/// Builds the recipient of a reclaimable pay-to-id (P2IDR) note.
+///
+/// `receiver_id` can consume the note at any time, while the note's sender can reclaim it once
+/// the chain reaches `reclaim_height`.
+pub fn build_p2idr_recipient(
+    receiver_id: AccountId,
+    reclaim_height: u32,
+    serial_num: Word,
+) -> Result<NoteRecipient, NoteError> {
+    let note_script = WellKnownNote::P2IDR.script();
+    let note_inputs = p2idr_inputs(receiver_id, reclaim_height)?;
+
+    Ok(NoteRecipient::new(serial_num, note_script, note_inputs))
+}
+
+/// Builds the inputs of a pay-to-id (P2ID) note for `receiver_id`.
+pub fn p2id_inputs(receiver_id: AccountId) -> Result<NoteInputs, NoteError> {
+    NoteInputs::new(vec![receiver_id.suffix(), receiver_id.prefix().as_felt()])
+}
+
+/// Builds the inputs of a reclaimable pay-to-id (P2IDR) note for `receiver_id`, reclaimable from
+/// block `reclaim_height` on.
+pub fn p2idr_inputs(receiver_id: AccountId, reclaim_height: u32) -> Result<NoteInputs, NoteError> {
+    NoteInputs::new(vec![
+        receiver_id.suffix(),
+        receiver_id.prefix().as_felt(),
+        Felt::from(reclaim_height),
+    ])
+}
+
+/// Reads the `aux` field of the metadata of the note with `note_id`, as stored by `client`.
+///
+/// Fails with [`ClientError::NoteNotFoundOnChain`] if the client doesn't know the note's metadata
+/// yet, i.e. before the note is on chain.
+pub async fn note_aux(client: &mut Client, note_id: NoteId) -> Result<Felt, ClientError> {
+    let note = client
+        .get_input_note(note_id)
+        .await?
+        .ok_or(ClientError::NoteNotFoundOnChain(note_id))?;
+    let metadata = note
+        .metadata()
+        .ok_or(ClientError::NoteNotFoundOnChain(note_id))?;
+
+    Ok(metadata.aux())
+}
+
+/// Asserts the note with `note_id`, as stored by `client`, has the recipient digest that
+/// [`build_p2id_recipient`] computes for `receiver_id` and `serial_num`.
+///
+/// The note should come from the chain, e.g. through [`await_note_on_chain`], so that the digest
+/// checked is the one the MASM actually emitted rather than one rebuilt in Rust.
+pub async fn assert_p2id_recipient(
+    client: &mut Client,
+    note_id: NoteId,
+    receiver_id: AccountId,
+    serial_num: Word,
+) {
+    let note = client
+        .get_input_note(note_id)
+        .await
+        .unwrap()
+        .expect("note should be tracked by the client");
+    let expected = build_p2id_recipient(receiver_id, serial_num).unwrap();
+
+    assert_eq!(
+        note.details().recipient().digest(),
+        expected.digest(),
+        "note {note_id} is not a P2ID note for {receiver_id}"
+    );
+}
+
+/// Returns the serial numbers of all notes known to `client`, keyed by note id.
+///
+/// Covers the notes created by the client's transactions as well as the ones it imported. Notes
+/// whose details the client doesn't have are skipped.
+pub async fn note_serial_nums(client: &mut Client) -> Result<BTreeMap<NoteId, Word>, ClientError> {
+    let mut serial_nums = BTreeMap::new();
+
+    for note in client.get_output_notes(NoteFilter::All).await? {
+        if let Some(recipient) = note.recipient() {
+            serial_nums.insert(note.id(), recipient.serial_num());
+        }
+    }
+    for note in client.get_input_notes(NoteFilter::All).await? {
+        serial_nums.insert(note.id(), note.details().serial_num());
+    }
+
+    Ok(serial_nums)
+}
+
+/// Proves `tx_result` locally and returns the proven transaction with the time proving took.
+///
+/// Relies on `testing_prove_transaction`, available through miden-client's `testing` feature.
+pub async fn prove_timed(
+    client: &mut Client,
+    tx_result: &TransactionResult,
+) -> Result<(ProvenTransaction, Duration), ClientError> {
+    let start_time = Instant::now();
+    let proven_tx = client.testing_prove_transaction(tx_result).await?;
+
+    Ok((proven_tx, start_time.elapsed()))
+}
+
+/// Builds the swap described by `swap_data` with a public SWAP note, executes it against
+/// `account_id` and proves it locally, without submitting.
+///
+/// Relies on `testing_prove_transaction`, available through miden-client's `testing` feature.
+pub async fn prove_swap(
+    client: &mut Client,
+    account_id: AccountId,
+    swap_data: &SwapTransactionData,
+) -> Result<(TransactionResult, ProvenTransaction), ClientError> {
+    let swap_request =
+        TransactionRequestBuilder::new().build_swap(swap_data, NoteType::Public, client.rng())?;
+    let tx_result = client.new_transaction(account_id, swap_request).await?;
+    let proven_tx = client.testing_prove_transaction(&tx_result).await?;
+
+    Ok((tx_result, proven_tx))
+}
+
+/// Runs `swap_data` through `build_swap` with a public SWAP note and returns that note, without
+/// any client or node involved.
+pub fn build_swap_note(swap_data: &SwapTransactionData) -> Result<Note, TransactionRequestError> {
+    let swap_request = TransactionRequestBuilder::new().build_swap(
+        swap_data,
+        NoteType::Public,
+        &mut random_coin(),
+    )?;
+
+    Ok(swap_request
+        .expected_output_notes()
+        .next()
+        .expect("the swap request should create a note")
+        .clone())
+}
+
+/// Decodes a SWAP note, as built by `build_swap`, into the asset it locks and the asset its
+/// inputs request in exchange.
+///
+/// The inputs of a SWAP note start with the digest of the payback note's recipient, followed by
+/// the word of the requested asset.
+pub fn swap_note_assets(note: &Note) -> (Asset, Asset) {
+    assert_eq!(
+        note.script().root(),
+        WellKnownNote::SWAP.script_root(),
+        "note {} is not a SWAP note",
+        note.id()
+    );
+
+    let offered_assets: Vec<Asset> = note.assets().iter().copied().collect();
+    let [offered_asset] = offered_assets[..] else {
+        panic!(
+            "SWAP note {} should lock a single asset, got {}",
+            note.id(),
+            offered_assets.len()
+        );
+    };
+
+    let inputs = note.inputs().values();
+    let requested_word: [Felt; 4] = inputs[4..8].try_into().unwrap();
+    let requested_asset = Asset::try_from(Word::from(requested_word)).unwrap();
+
+    (offered_asset, requested_asset)
+}
+
+/// Runs `body` against `client` and asserts it changed the `faucet_id` balance of `account_id`
+/// by exactly `expected_delta`, which is negative when tokens leave the account.
+///
+/// Returns whatever `body` returns.
+pub async fn assert_balance_delta<T>(
+    client: &mut Client,
+    account_id: AccountId,
+    faucet_id: AccountId,
+    expected_delta: i64,
+    body: impl AsyncFnOnce(&mut Client) -> T,
+) -> T {
+    let before = balance(client, account_id, faucet_id).await.unwrap();
+    let output = body(client).await;
+    client.sync_state().await.unwrap();
+    let after = balance(client, account_id, faucet_id).await.unwrap();
+
+    let delta = after as i64 - before as i64;
+    assert_eq!(
+        delta, expected_delta,
+        "balance of {account_id} went from {before} to {after}"
+    );
+
+    output
+}
+
+/// Returns the nonce of `account_id` as currently tracked by `client`.
+pub async fn account_nonce(client: &mut Client, account_id: AccountId) -> Result<u64, ClientError> {
+    let account = client
+        .get_account(account_id)
+        .await?
+        .ok_or(ClientError::AccountDataNotFound(account_id))?;
+
+    Ok(account.account().nonce().as_int())
+}
+
+/// Asserts `tx` created exactly one P2ID note per `(receiver, amount)` pair in `expected`, in any
+/// order.
+///
+/// The receiver is decoded from the note inputs and the amount sums all fungible assets of the
+/// note, so every output note must be a full note.
+pub fn assert_output_notes(tx: &TransactionResult, expected: &[(AccountId, u64)]) {
+    let mut actual: Vec<(AccountId, u64)> = tx
+        .created_notes()
+        .iter()
+        .map(|output_note| {
+            let OutputNote::Full(note) = output_note else {
+                panic!("details of output note {} are unknown", output_note.id());
+            };
+            let inputs = note.inputs().values();
+            assert!(
+                inputs.len() >= 2,
+                "output note {} is not addressed to an account",
+                note.id()
+            );
+            let receiver_id = AccountId::try_from([inputs[1], inputs[0]]).unwrap();
+            let amount = note
+                .assets()
+                .iter()
+                .map(|asset| match asset {
+                    Asset::Fungible(asset) => asset.amount(),
+                    Asset::NonFungible(_) => 0,
+                })
+                .sum();
+
+            (receiver_id, amount)
+        })
+        .collect();
+    let mut expected = expected.to_vec();
+
+    actual.sort();
+    expected.sort();
+    assert_eq!(actual, expected, "unexpected output notes");
+}
+
+/// Asserts `tx` consumed exactly the notes with ids in `expected`, in any order.
+pub fn assert_consumed_notes(tx: &TransactionResult, expected: &[NoteId]) {
+    let mut actual: Vec<NoteId> = tx.consumed_notes().iter().map(|note| note.id()).collect();
+    let mut expected = expected.to_vec();
+
+    actual.sort();
+    expected.sort();
+    assert_eq!(actual, expected, "unexpected consumed notes");
+}
+
+/// State of a single account within a [`ClientSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSnapshot {
+    pub nonce: u64,
+    /// Fungible balances, keyed by faucet id.
+    pub balances: BTreeMap<AccountId, u64>,
+}
+
+/// What a client knows at one point in time: its tracked accounts and the ids of its notes.
+///
+/// Compare two snapshots with [`ClientSnapshot::diff`] to get a readable report of what changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSnapshot {
+    pub accounts: BTreeMap<AccountId, AccountSnapshot>,
+    pub input_notes: BTreeSet<NoteId>,
+    pub output_notes: BTreeSet<NoteId>,
+}
+
+/// Captures the accounts and notes currently tracked by `client`.
+pub async fn snapshot(client: &mut Client) -> Result<ClientSnapshot, ClientError> {
+    let mut accounts = BTreeMap::new();
+    for (header, _) in client.get_account_headers().await? {
+        let account_id = header.id();
+        let account = client
+            .get_account(account_id)
+            .await?
+            .ok_or(ClientError::AccountDataNotFound(account_id))?;
+        let balances = account
+            .account()
+            .vault()
+            .assets()
+            .filter_map(|asset| match asset {
+                Asset::Fungible(asset) => Some((asset.faucet_id(), asset.amount())),
+                Asset::NonFungible(_) => None,
+            })
+            .collect();
+
+        accounts.insert(
+            account_id,
+            AccountSnapshot {
+                nonce: header.nonce().as_int(),
+                balances,
+            },
+        );
+    }
+
+    let input_notes = client
+        .get_input_notes(NoteFilter::All)
+        .await?
+        .iter()
+        .map(|note| note.id())
+        .collect();
+    let output_notes = client
+        .get_output_notes(NoteFilter::All)
+        .await?
+        .iter()
+        .map(|note| note.id())
+        .collect();
+
+    Ok(ClientSnapshot {
+        accounts,
+        input_notes,
+        output_notes,
+    })
+}
+
+impl ClientSnapshot {
+    /// Describes what changed from `self` to `after`, one change per line. Empty if nothing did.
+    pub fn diff(&self, after: &ClientSnapshot) -> String {
+        let mut lines = Vec::new();
+
+        for (account_id, before) in &self.accounts {
+            let Some(now) = after.accounts.get(account_id) else {
+                lines.push(format!("- account {account_id}"));
+                continue;
+            };
+            if before.nonce != now.nonce {
+                lines.push(format!(
+                    "~ account {account_id}: nonce {} -> {}",
+                    before.nonce, now.nonce
+                ));
+            }
+            let faucet_ids: BTreeSet<_> =
+                before.balances.keys().chain(now.balances.keys()).collect();
+            for faucet_id in faucet_ids {
+                let old = before.balances.get(faucet_id).copied().unwrap_or(0);
+                let new = now.balances.get(faucet_id).copied().unwrap_or(0);
+                if old != new {
+                    lines.push(format!(
+                        "~ account {account_id}: balance of {faucet_id} {old} -> {new}"
+                    ));
+                }
+            }
+        }
+        for account_id in after.accounts.keys() {
+            if !self.accounts.contains_key(account_id) {
+                lines.push(format!("+ account {account_id}"));
+            }
+        }
+
+        for (kind, before, now) in [
+            ("input note", &self.input_notes, &after.input_notes),
+            ("output note", &self.output_notes, &after.output_notes),
+        ] {
+            lines.extend(before.difference(now).map(|id| format!("- {kind} {id}")));
+            lines.extend(now.difference(before).map(|id| format!("+ {kind} {id}")));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for ClientSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (account_id, account) in &self.accounts {
+            writeln!(f, "account {account_id} (nonce {})", account.nonce)?;
+            for (faucet_id, amount) in &account.balances {
+                writeln!(f, "  {amount} of {faucet_id}")?;
+            }
+        }
+        writeln!(f, "input notes: {}", self.input_notes.len())?;
+        write!(f, "output notes: {}", self.output_notes.len())
+    }
+}